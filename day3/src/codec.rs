@@ -0,0 +1,203 @@
+//! A compact, versioned binary format for the day3 data structures.
+//!
+//! Every encoding starts with a header: the magic bytes `DAY3`, the format
+//! version, a byte naming the kind of structure and the number of values as a
+//! little-endian `u64`. The values follow, each in the encoding of its
+//! [`Encode`] impl.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+const MAGIC: [u8; 4] = *b"DAY3";
+const VERSION: u8 = 1;
+
+/// The kind of structure stored after the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    BinaryTree = b'T' as isize,
+    List = b'L' as isize,
+}
+
+/// Why decoding failed.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The reader failed for a reason other than running out of input.
+    Io(io::Error),
+    /// The input ended before the encoded structure did.
+    Truncated,
+    /// The input does not start with the magic bytes.
+    BadMagic,
+    /// The input was written by an unknown version of the format.
+    UnsupportedVersion(u8),
+    /// The input holds a different kind of structure than the one requested.
+    WrongKind { expected: Kind, found: u8 },
+    /// The input is complete but describes an impossible structure.
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Io(err) => write!(f, "read failed: {err}"),
+            DecodeError::Truncated => write!(f, "input is truncated"),
+            DecodeError::BadMagic => write!(f, "input is not in the day3 format"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            DecodeError::WrongKind { expected, found } => {
+                write!(f, "expected a {expected:?}, found kind {found:#04x}")
+            }
+            DecodeError::Corrupt(reason) => write!(f, "input is corrupt: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            DecodeError::Truncated
+        } else {
+            DecodeError::Io(err)
+        }
+    }
+}
+
+/// A value that can be written in the binary format.
+pub trait Encode {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+}
+
+/// A value that can be read back from the binary format.
+pub trait Decode: Sized {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, DecodeError>;
+}
+
+macro_rules! impl_codec_for_int {
+    ($($int:ty),*) => {
+        $(
+            impl Encode for $int {
+                fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+            }
+
+            impl Decode for $int {
+                fn decode<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+                    let mut bytes = [0; size_of::<$int>()];
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$int>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_for_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+/// Strings are stored as their length in bytes followed by their UTF-8 bytes.
+impl Encode for String {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (self.len() as u64).encode(writer)?;
+        writer.write_all(self.as_bytes())
+    }
+}
+
+impl Decode for String {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let len = u64::decode(reader)?;
+        // read through `take` rather than allocating `len` bytes up front, so
+        // a corrupt length cannot exhaust memory
+        let mut bytes = Vec::new();
+        reader.take(len).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < len {
+            return Err(DecodeError::Truncated);
+        }
+        String::from_utf8(bytes).map_err(|_| DecodeError::Corrupt("string is not UTF-8"))
+    }
+}
+
+pub(crate) fn write_header<W: Write>(writer: &mut W, kind: Kind, len: usize) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[VERSION, kind as u8])?;
+    (len as u64).encode(writer)
+}
+
+/// Check the header for `kind` and return the number of values that follow.
+pub(crate) fn read_header<R: Read>(reader: &mut R, kind: Kind) -> Result<u64, DecodeError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(DecodeError::BadMagic);
+    }
+    let version = u8::decode(reader)?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    let found = u8::decode(reader)?;
+    if found != kind as u8 {
+        return Err(DecodeError::WrongKind {
+            expected: kind,
+            found,
+        });
+    }
+    u64::decode(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_round_trip_and_errors() {
+        let mut bytes = Vec::new();
+        write_header(&mut bytes, Kind::List, 3).unwrap();
+        assert_eq!(read_header(&mut bytes.as_slice(), Kind::List).unwrap(), 3);
+        assert!(matches!(
+            read_header(&mut bytes.as_slice(), Kind::BinaryTree),
+            Err(DecodeError::WrongKind {
+                expected: Kind::BinaryTree,
+                found: b'L'
+            })
+        ));
+        assert!(matches!(
+            read_header(&mut &bytes[..7], Kind::List),
+            Err(DecodeError::Truncated)
+        ));
+        assert!(matches!(
+            read_header(&mut &b"JSON{}"[..], Kind::List),
+            Err(DecodeError::BadMagic)
+        ));
+        bytes[4] = 9;
+        assert!(matches!(
+            read_header(&mut bytes.as_slice(), Kind::List),
+            Err(DecodeError::UnsupportedVersion(9))
+        ));
+    }
+
+    #[test]
+    fn strings() {
+        let mut bytes = Vec::new();
+        String::from("Löwe").encode(&mut bytes).unwrap();
+        assert_eq!(String::decode(&mut bytes.as_slice()).unwrap(), "Löwe");
+        assert!(matches!(
+            String::decode(&mut &bytes[..bytes.len() - 1]),
+            Err(DecodeError::Truncated)
+        ));
+        let mut bytes = Vec::new();
+        2u64.encode(&mut bytes).unwrap();
+        bytes.extend([0xff, 0xfe]);
+        assert!(matches!(
+            String::decode(&mut bytes.as_slice()),
+            Err(DecodeError::Corrupt(_))
+        ));
+    }
+}
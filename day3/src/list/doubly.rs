@@ -0,0 +1,417 @@
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+
+// Forward links own the next node; backward links are `Weak`, so the nodes
+// never form a reference cycle and are freed as soon as the list lets go.
+type Link<T> = Rc<RefCell<Node<T>>>;
+type BackLink<T> = Weak<RefCell<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    next: Option<Link<T>>,
+    prev: Option<BackLink<T>>,
+}
+
+/// A doubly-linked list built from `Rc<RefCell<_>>` forward links and `Weak`
+/// back links, edited in the middle through a [`CursorMut`].
+pub struct DoublyLinkedList<T> {
+    head: Option<Link<T>>,
+    tail: Option<BackLink<T>>,
+    len: usize,
+}
+
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            next: self.head.take(),
+            prev: None,
+        }));
+        match &node.borrow().next {
+            Some(next) => next.borrow_mut().prev = Some(Rc::downgrade(&node)),
+            None => self.tail = Some(Rc::downgrade(&node)),
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            next: None,
+            prev: self.tail.take(),
+        }));
+        self.tail = Some(Rc::downgrade(&node));
+        match node.borrow().prev.as_ref().and_then(Weak::upgrade) {
+            Some(prev) => prev.borrow_mut().next = Some(Rc::clone(&node)),
+            None => self.head = Some(Rc::clone(&node)),
+        }
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let node = self.head.take()?;
+        match node.borrow_mut().next.take() {
+            Some(next) => {
+                next.borrow_mut().prev = None;
+                self.head = Some(next);
+            }
+            None => self.tail = None,
+        }
+        self.len -= 1;
+        Some(into_value(node))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let node = self.tail.take()?.upgrade()?;
+        let prev = node
+            .borrow_mut()
+            .prev
+            .take()
+            .and_then(|prev| prev.upgrade());
+        // drop the strong link that owned the node
+        match &prev {
+            Some(prev) => prev.borrow_mut().next = None,
+            None => self.head = None,
+        }
+        self.tail = prev.as_ref().map(Rc::downgrade);
+        self.len -= 1;
+        Some(into_value(node))
+    }
+
+    pub fn front(&self) -> Option<Ref<'_, T>> {
+        let node = self.head.as_ref()?;
+        Some(Ref::map(node.borrow(), |node| &node.value))
+    }
+
+    pub fn front_mut(&mut self) -> Option<RefMut<'_, T>> {
+        let node = self.head.as_ref()?;
+        Some(RefMut::map(node.borrow_mut(), |node| &mut node.value))
+    }
+
+    /// A cursor positioned on the first element. When the list is empty it
+    /// starts on the "ghost" position between the back and the front.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let current = self.head.clone();
+        CursorMut {
+            list: self,
+            current,
+        }
+    }
+
+    /// A cursor positioned on the last element.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let current = self.tail.as_ref().and_then(Weak::upgrade);
+        CursorMut {
+            list: self,
+            current,
+        }
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        // one node at a time, so long lists do not drop recursively
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for value in iter {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+/// Take the value out of a node that has just been unlinked.
+fn into_value<T>(node: Link<T>) -> T {
+    match Rc::try_unwrap(node) {
+        Ok(node) => node.into_inner().value,
+        Err(_) => unreachable!("an unlinked node has no other owner"),
+    }
+}
+
+/// A cursor over a [`DoublyLinkedList`] that can move in both directions and
+/// edit the list at its position.
+///
+/// Besides the elements, the cursor can sit on a "ghost" position that lies
+/// between the last and the first element, as with the cursors of
+/// `std::collections::LinkedList`.
+pub struct CursorMut<'a, T> {
+    list: &'a mut DoublyLinkedList<T>,
+    // `None` on the ghost position
+    current: Option<Link<T>>,
+}
+
+// The cursor holds a strong reference to its node, so it must keep the list
+// borrowed until it is gone; otherwise a leftover cursor could keep a node
+// that the list has since unlinked alive.
+impl<T> Drop for CursorMut<'_, T> {
+    fn drop(&mut self) {
+        self.current = None;
+    }
+}
+
+impl<T> CursorMut<'_, T> {
+    /// The element under the cursor, or `None` on the ghost position.
+    pub fn current(&self) -> Option<Ref<'_, T>> {
+        let node = self.current.as_ref()?;
+        Some(Ref::map(node.borrow(), |node| &node.value))
+    }
+
+    pub fn current_mut(&mut self) -> Option<RefMut<'_, T>> {
+        let node = self.current.as_ref()?;
+        Some(RefMut::map(node.borrow_mut(), |node| &mut node.value))
+    }
+
+    /// Move to the next element; from the last element onto the ghost
+    /// position, and from there to the first element.
+    pub fn move_next(&mut self) {
+        self.current = match &self.current {
+            Some(node) => node.borrow().next.clone(),
+            None => self.list.head.clone(),
+        };
+    }
+
+    /// Move to the previous element; from the first element onto the ghost
+    /// position, and from there to the last element.
+    pub fn move_prev(&mut self) {
+        self.current = match &self.current {
+            Some(node) => node.borrow().prev.as_ref().and_then(Weak::upgrade),
+            None => self.list.tail.as_ref().and_then(Weak::upgrade),
+        };
+    }
+
+    /// Insert `value` after the cursor; on the ghost position it becomes the
+    /// first element.
+    pub fn insert_after(&mut self, value: T) {
+        let Some(current) = &self.current else {
+            self.list.push_front(value);
+            return;
+        };
+        let next = current.borrow_mut().next.take();
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            next,
+            prev: Some(Rc::downgrade(current)),
+        }));
+        match &node.borrow().next {
+            Some(next) => next.borrow_mut().prev = Some(Rc::downgrade(&node)),
+            None => self.list.tail = Some(Rc::downgrade(&node)),
+        }
+        current.borrow_mut().next = Some(node);
+        self.list.len += 1;
+    }
+
+    /// Insert `value` before the cursor; on the ghost position it becomes the
+    /// last element.
+    pub fn insert_before(&mut self, value: T) {
+        let Some(current) = &self.current else {
+            self.list.push_back(value);
+            return;
+        };
+        let Some(prev) = current.borrow().prev.as_ref().and_then(Weak::upgrade) else {
+            self.list.push_front(value);
+            return;
+        };
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            next: Some(Rc::clone(current)),
+            prev: Some(Rc::downgrade(&prev)),
+        }));
+        current.borrow_mut().prev = Some(Rc::downgrade(&node));
+        prev.borrow_mut().next = Some(node);
+        self.list.len += 1;
+    }
+
+    /// Remove the element under the cursor and return it, moving the cursor
+    /// to the next element. Does nothing on the ghost position.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current.take()?;
+        let prev = node
+            .borrow_mut()
+            .prev
+            .take()
+            .and_then(|prev| prev.upgrade());
+        let next = node.borrow_mut().next.take();
+        match &next {
+            Some(next) => next.borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.list.tail = prev.as_ref().map(Rc::downgrade),
+        }
+        // replacing the link that pointed at the node releases it
+        match &prev {
+            Some(prev) => prev.borrow_mut().next = next.clone(),
+            None => self.list.head = next.clone(),
+        }
+        self.current = next;
+        self.list.len -= 1;
+        Some(into_value(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::on_small_stack;
+
+    fn contents<T: Clone>(list: &DoublyLinkedList<T>) -> Vec<T> {
+        let mut values = Vec::new();
+        let mut next = list.head.clone();
+        while let Some(node) = next {
+            values.push(node.borrow().value.clone());
+            next = node.borrow().next.clone();
+        }
+        // walking backwards must give the same elements
+        let mut backwards = Vec::new();
+        let mut prev = list.tail.as_ref().and_then(Weak::upgrade);
+        while let Some(node) = prev {
+            backwards.push(node.borrow().value.clone());
+            prev = node.borrow().prev.as_ref().and_then(Weak::upgrade);
+        }
+        backwards.reverse();
+        assert_eq!(values.len(), backwards.len());
+        assert_eq!(values.len(), list.len());
+        values
+    }
+
+    fn weak_nodes<T>(list: &DoublyLinkedList<T>) -> Vec<BackLink<T>> {
+        let mut nodes = Vec::new();
+        let mut next = list.head.clone();
+        while let Some(node) = next {
+            nodes.push(Rc::downgrade(&node));
+            next = node.borrow().next.clone();
+        }
+        nodes
+    }
+
+    #[test]
+    fn push_and_pop_both_ends() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(contents(&list), [1, 2, 3]);
+        assert_eq!(*list.front().unwrap(), 1);
+        *list.front_mut().unwrap() = 10;
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn cursor_moves_both_ways() {
+        let mut list: DoublyLinkedList<i32> = (1..=3).collect();
+        let mut cursor = list.cursor_front_mut();
+        assert_eq!(*cursor.current().unwrap(), 1);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(*cursor.current().unwrap(), 3);
+        cursor.move_next();
+        assert!(cursor.current().is_none());
+        cursor.move_next();
+        assert_eq!(*cursor.current().unwrap(), 1);
+        cursor.move_prev();
+        assert!(cursor.current().is_none());
+        cursor.move_prev();
+        assert_eq!(*cursor.current().unwrap(), 3);
+        *cursor.current_mut().unwrap() = 30;
+        drop(cursor);
+        assert_eq!(contents(&list), [1, 2, 30]);
+    }
+
+    #[test]
+    fn cursor_edits() {
+        let mut list: DoublyLinkedList<i32> = [1, 3, 5].into_iter().collect();
+        {
+            let mut cursor = list.cursor_front_mut();
+            cursor.insert_before(0);
+            cursor.insert_after(2);
+            cursor.move_next();
+            cursor.move_next();
+            cursor.insert_after(4);
+            cursor.move_prev();
+            cursor.move_prev();
+            // on 1
+            assert_eq!(cursor.remove_current(), Some(1));
+            assert_eq!(*cursor.current().unwrap(), 2);
+        }
+        assert_eq!(contents(&list), [0, 2, 3, 4, 5]);
+
+        {
+            let mut cursor = list.cursor_back_mut();
+            assert_eq!(cursor.remove_current(), Some(5));
+            // past the end: ghost position
+            assert!(cursor.current().is_none());
+            assert_eq!(cursor.remove_current(), None);
+            cursor.insert_before(6);
+            cursor.insert_after(-1);
+        }
+        assert_eq!(contents(&list), [-1, 0, 2, 3, 4, 6]);
+
+        let mut cursor = list.cursor_front_mut();
+        while cursor.remove_current().is_some() {}
+        drop(cursor);
+        assert!(list.is_empty());
+        assert!(list.head.is_none() && list.tail.is_none());
+    }
+
+    #[test]
+    fn nodes_are_freed() {
+        let mut list: DoublyLinkedList<String> =
+            ["a", "b", "c", "d"].into_iter().map(String::from).collect();
+        let nodes = weak_nodes(&list);
+        assert!(nodes.iter().all(|node| node.upgrade().is_some()));
+
+        // unlinked nodes are freed right away
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.remove_current();
+        drop(cursor);
+        list.pop_back();
+        assert!(nodes[1].upgrade().is_none());
+        assert!(nodes[3].upgrade().is_none());
+        assert_eq!(contents(&list), ["a", "c"]);
+
+        // and the rest once the list is dropped
+        drop(list);
+        assert!(nodes.iter().all(|node| node.upgrade().is_none()));
+    }
+
+    #[test]
+    fn drop_long_list_on_small_stack() {
+        on_small_stack(|| {
+            let list: DoublyLinkedList<u32> = (0..1_000_000).collect();
+            let nodes = weak_nodes(&list);
+            drop(list);
+            assert!(nodes[0].upgrade().is_none());
+        });
+    }
+}
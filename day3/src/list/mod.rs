@@ -0,0 +1,357 @@
+pub mod doubly;
+pub mod persistent;
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::codec::{self, Decode, DecodeError, Encode, Kind};
+
+/// The cells a [`List`] is made of. Only [`List`] may own a chain of them:
+/// the derived drop glue recurses once per cell, so the iterative drop of
+/// [`List`] has to take the chain apart.
+#[derive(Debug)]
+enum Link<T> {
+    /// A non-empty list: first element and the rest of the list.
+    Element(T, Box<Link<T>>),
+    /// An empty list.
+    Nil,
+}
+
+/// A singly-linked list of [`Link`] cells that knows its length.
+pub struct List<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        Self {
+            head: Link::Nil,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let rest = std::mem::replace(&mut self.head, Link::Nil);
+        self.head = Link::Element(value, Box::new(rest));
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        match std::mem::replace(&mut self.head, Link::Nil) {
+            Link::Element(value, tail) => {
+                self.head = *tail;
+                self.len -= 1;
+                Some(value)
+            }
+            Link::Nil => None,
+        }
+    }
+
+    /// The first element, if any.
+    pub fn peek(&self) -> Option<&T> {
+        match &self.head {
+            Link::Element(value, _) => Some(value),
+            Link::Nil => None,
+        }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        match &mut self.head {
+            Link::Element(value, _) => Some(value),
+            Link::Nil => None,
+        }
+    }
+
+    /// Reverse the order of the elements in place, without reallocating.
+    pub fn reverse(&mut self) {
+        let mut rest = std::mem::replace(&mut self.head, Link::Nil);
+        let mut reversed = Link::Nil;
+        while let Link::Element(value, mut tail) = rest {
+            rest = std::mem::replace(&mut *tail, reversed);
+            reversed = Link::Element(value, tail);
+        }
+        self.head = reversed;
+    }
+
+    /// Move all elements of `other` to the end of `self`, leaving `other`
+    /// empty.
+    pub fn append(&mut self, other: &mut List<T>) {
+        let mut end = &mut self.head;
+        while let Link::Element(_, tail) = end {
+            end = tail;
+        }
+        *end = std::mem::replace(&mut other.head, Link::Nil);
+        self.len += std::mem::take(&mut other.len);
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: &self.head }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: Some(&mut self.head),
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // unlink one element at a time; the derived drop glue would recurse
+        // once per element and overflow the stack on long lists
+        let mut rest = std::mem::replace(&mut self.head, Link::Nil);
+        while let Link::Element(_, tail) = rest {
+            rest = *tail;
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        // link the elements up from the back
+        let mut list = List::new();
+        for value in values.into_iter().rev() {
+            list.push_front(value);
+        }
+        list
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Formats the list as `[1, 2, 3]`.
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{value}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Iterator over references to the elements, created by [`List::iter`].
+pub struct Iter<'a, T> {
+    next: &'a Link<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next {
+            Link::Element(value, tail) => {
+                self.next = tail;
+                Some(value)
+            }
+            Link::Nil => None,
+        }
+    }
+}
+
+/// Iterator over mutable references to the elements, created by
+/// [`List::iter_mut`].
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Link<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next.take()? {
+            Link::Element(value, tail) => {
+                self.next = Some(tail);
+                Some(value)
+            }
+            Link::Nil => None,
+        }
+    }
+}
+
+/// Owning iterator over the elements, created by calling `into_iter` on a
+/// [`List`].
+pub struct IntoIter<T>(List<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// The elements are stored front to back.
+impl<T: Encode> Encode for List<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        codec::write_header(writer, Kind::List, self.len)?;
+        for value in self {
+            value.encode(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Decode> Decode for List<T> {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let len = codec::read_header(reader, Kind::List)?;
+        let mut values = Vec::new();
+        for _ in 0..len {
+            values.push(T::decode(reader)?);
+        }
+        Ok(values.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::on_small_stack;
+
+    /// A list that owns a chain of cells built by hand.
+    fn from_link<T>(head: Link<T>) -> List<T> {
+        let mut len = 0;
+        let mut rest = &head;
+        while let Link::Element(_, tail) = rest {
+            len += 1;
+            rest = tail;
+        }
+        List { head, len }
+    }
+
+    #[test]
+    fn push_pop_peek() {
+        let mut list = List::new();
+        assert_eq!(list.pop_front(), None);
+        list.push_front(3);
+        list.push_front(2);
+        list.push_front(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek(), Some(&1));
+        if let Some(first) = list.peek_mut() {
+            *first = 10;
+        }
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.to_string(), "[3]");
+    }
+
+    #[test]
+    fn iterate_reverse_append() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+        for value in &mut list {
+            *value *= 10;
+        }
+        list.reverse();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [30, 20, 10]);
+
+        let mut other: List<i32> = [5, 6].into_iter().collect();
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(list.len(), 5);
+        assert_eq!(format!("{list:?}"), "[30, 20, 10, 5, 6]");
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [30, 20, 10, 5, 6]);
+
+        let mut empty: List<i32> = List::new();
+        empty.reverse();
+        empty.append(&mut List::new());
+        assert_eq!(empty.to_string(), "[]");
+    }
+
+    #[test]
+    fn from_links() {
+        let list = from_link(Link::Element(
+            1,
+            Box::new(Link::Element(2, Box::new(Link::Nil))),
+        ));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.to_string(), "[1, 2]");
+    }
+
+    #[test]
+    fn drop_long_list_on_small_stack() {
+        on_small_stack(|| {
+            let mut list = List::new();
+            for value in 0..2_000_000 {
+                list.push_front(value);
+            }
+            list.reverse();
+            assert_eq!(list.peek(), Some(&0));
+            drop(list);
+
+            let mut link = Link::Nil;
+            for value in 0..2_000_000 {
+                link = Link::Element(value, Box::new(link));
+            }
+            drop(from_link(link));
+        });
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        let list: List<u32> = [1, 2].into_iter().collect();
+        let mut bytes = Vec::new();
+        list.encode(&mut bytes).unwrap();
+        let decoded = List::<u32>::decode(&mut bytes.as_slice()).unwrap();
+        assert_eq!(decoded.to_string(), list.to_string());
+
+        assert!(matches!(
+            List::<u32>::decode(&mut &bytes[..bytes.len() - 1]),
+            Err(DecodeError::Truncated)
+        ));
+        assert!(matches!(
+            List::<u64>::decode(&mut bytes.as_slice()),
+            Err(DecodeError::Truncated)
+        ));
+    }
+}
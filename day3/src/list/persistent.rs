@@ -0,0 +1,172 @@
+use std::fmt;
+use std::rc::Rc;
+
+struct Node<T> {
+    value: T,
+    next: Option<Rc<Node<T>>>,
+}
+
+/// An immutable cons list whose tails are shared through `Rc`.
+///
+/// Unlike [`List`](super::List), which owns its tail exclusively, many lists
+/// can share the same tail: `prepend` creates a new list in O(1) that points
+/// at the old one instead of copying it.
+pub struct PersistentList<T> {
+    head: Option<Rc<Node<T>>>,
+}
+
+impl<T> PersistentList<T> {
+    pub fn new() -> Self {
+        Self { head: None }
+    }
+
+    /// A new list with `value` in front of the elements of this one, which
+    /// are shared rather than copied.
+    pub fn prepend(&self, value: T) -> Self {
+        Self {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    /// The first element, if any.
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    /// The list without its first element, sharing the remaining elements.
+    pub fn tail(&self) -> Self {
+        Self {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// The number of elements, counted in O(n).
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Cloning a list only bumps the reference count of its first node.
+impl<T> Clone for PersistentList<T> {
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> Drop for PersistentList<T> {
+    fn drop(&mut self) {
+        // free the nodes this list is the last owner of, one at a time, and
+        // stop at the first node that another list still shares
+        let mut head = self.head.take();
+        while let Some(node) = head {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => head = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the elements, created by [`PersistentList::iter`].
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::on_small_stack;
+
+    fn first_node<T>(list: &PersistentList<T>) -> &Rc<Node<T>> {
+        list.head.as_ref().unwrap()
+    }
+
+    #[test]
+    fn head_tail_and_iteration() {
+        let empty = PersistentList::new();
+        let list = empty.prepend(3).prepend(2).prepend(1);
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(list.tail().head(), Some(&2));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(list.len(), 3);
+        assert!(empty.is_empty());
+        assert!(empty.tail().is_empty());
+        assert_eq!(format!("{:?}", list.tail()), "[2, 3]");
+    }
+
+    #[test]
+    fn tails_are_shared() {
+        let shared = PersistentList::new().prepend("c").prepend("b");
+        assert_eq!(Rc::strong_count(first_node(&shared)), 1);
+
+        let a = shared.prepend("a");
+        let x = shared.prepend("x");
+        // `shared` itself plus the two lists built on top of it
+        assert_eq!(Rc::strong_count(first_node(&shared)), 3);
+        assert!(Rc::ptr_eq(first_node(&a.tail()), first_node(&x.tail())));
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(x.iter().copied().collect::<Vec<_>>(), ["x", "b", "c"]);
+
+        drop(a);
+        assert_eq!(Rc::strong_count(first_node(&shared)), 2);
+        drop(x);
+        assert_eq!(Rc::strong_count(first_node(&shared)), 1);
+    }
+
+    #[test]
+    fn drop_long_list_on_small_stack() {
+        on_small_stack(|| {
+            let mut list = PersistentList::new();
+            for value in 0..1_000_000 {
+                list = list.prepend(value);
+            }
+            let tail = list.tail();
+            drop(list);
+            assert_eq!(tail.head(), Some(&999_998));
+        });
+    }
+}
@@ -1,8 +1,19 @@
+pub mod codec;
+pub mod list;
+pub mod package;
+pub mod tests;
+pub mod tree;
+
 use std::{
     cell::{Cell, RefCell},
     rc::Rc,
 };
 
+use list::List;
+use package::resolve::Registry;
+use package::{Language, PackageBuilder};
+use tree::BinaryTree;
+
 #[derive(Debug)]
 struct Point(i32, i32);
 
@@ -19,86 +30,13 @@ impl Drop for WaitToDrop {
     }
 }
 
+/// A cons list spelled out by hand, to show a recursive type behind a `Box`.
+/// [`List`] is the real thing: it is built from the same kind of cells but
+/// frees long lists without recursing.
 #[derive(Debug)]
-enum Language {
-    Rust,
-    Java,
-    Perl,
-}
-
-#[derive(Clone, Debug)]
-struct Dependency {
-    name: String,
-    version_expression: String,
-}
-
-/// A representation of a software package
-#[derive(Debug)]
-struct Package {
-    name: String,
-    version: String,
-    authors: Vec<String>,
-    dependencies: Vec<Dependency>,
-    language: Option<Language>,
-}
-
-impl Package {
-    /// Return a representation of this package as a dependency, for use in
-    /// building other packages.
-    fn as_dependency(&self) -> Dependency {
-        Dependency {
-            name: self.name.clone(),
-            version_expression: self.version.clone(),
-        }
-    }
-}
-
-struct PackageBuilder(Package);
-
-impl PackageBuilder {
-    fn new(name: impl Into<String>) -> Self {
-        Self(Package {
-            name: name.into(),
-            version: String::new(),
-            authors: Vec::new(),
-            dependencies: Vec::new(),
-            language: None,
-        })
-    }
-
-    /// Set the package version.
-    fn version(mut self, version: impl Into<String>) -> Self {
-        self.0.version = version.into();
-        self
-    }
-
-    /// Set the package authors.
-    fn authors(mut self, authors: Vec<String>) -> Self {
-        self.0.authors = authors;
-        self
-    }
-
-    /// Add an additional dependency.
-    fn dependency(mut self, dependency: Dependency) -> Self {
-        self.0.dependencies.push(dependency);
-        self
-    }
-
-    /// Set the language. If not set, language defaults to None.
-    fn language(mut self, language: Language) -> Self {
-        self.0.language = Some(language);
-        self
-    }
-
-    fn build(self) -> Package {
-        self.0
-    }
-}
-
-#[derive(Debug)]
-enum List<T> {
+enum Cons<T> {
     /// A non-empty list: first element and the rest of the list.
-    Element(T, Box<List<T>>),
+    Element(T, Box<Cons<T>>),
     /// An empty list.
     Nil,
 }
@@ -128,86 +66,6 @@ impl Pet for Cat {
     }
 }
 
-#[derive(Debug)]
-struct Node<T: Ord> {
-    value: T,
-    left: SubTree<T>,
-    right: SubTree<T>,
-}
-
-#[derive(Debug)]
-struct SubTree<T: Ord>(Option<Box<Node<T>>>);
-
-#[derive(Debug)]
-pub struct BinaryTree<T: Ord> {
-    root: SubTree<T>,
-}
-
-impl<T: Ord> BinaryTree<T> {
-    fn new() -> Self {
-        Self {
-            root: SubTree(None),
-        }
-    }
-
-    fn insert(&mut self, value: T) {
-        self.root.insert(value);
-    }
-
-    fn has(&self, value: &T) -> bool {
-        self.root.has(value)
-    }
-
-    fn len(&self) -> usize {
-        self.root.len()
-    }
-}
-
-impl<T: Ord> SubTree<T> {
-    fn insert(&mut self, value: T) {
-        match self {
-            // if the node is empty, itself is the node
-            SubTree(None) => {
-                *self = SubTree(Some(Box::new(Node {
-                    value,
-                    left: SubTree(None),
-                    right: SubTree(None),
-                })));
-            }
-            SubTree(Some(node)) => {
-                if value < node.value {
-                    node.left.insert(value);
-                } else if value > node.value {
-                    node.right.insert(value);
-                }
-            }
-        }
-    }
-
-    fn has(&self, value: &T) -> bool {
-        match self {
-            SubTree(None) => false,
-            SubTree(Some(node)) => {
-                if value == &node.value {
-                    true
-                } else if value < &node.value {
-                    node.left.has(value)
-                } else {
-                    node.right.has(value)
-                }
-            }
-        }
-    }
-
-    fn len(&self) -> usize {
-        match self {
-            SubTree(None) => 0,
-            // recursive call to the left and right subtree
-            SubTree(Some(node)) => 1 + node.left.len() + node.right.len(),
-        }
-    }
-}
-
 fn left_most<'a>(p1: &'a Point, p2: &'a Point) -> &'a Point {
     if p1.0 < p2.0 { p1 } else { p2 }
 }
@@ -324,21 +182,32 @@ fn main() {
     // If it did, std::mem::drop would be called at the end of the block, resulting in another call to Drop::drop, and a stack overflow!
 
     // 8、exercise： Builder Type
-    let base64 = PackageBuilder::new("base64").version("0.13").build();
+    let base64 = PackageBuilder::new("base64")
+        .version("0.13.0")
+        .build()
+        .unwrap();
     dbg!(&base64);
     let log = PackageBuilder::new("log")
-        .version("0.4")
+        .version("0.4.0")
         .language(Language::Rust)
-        .build();
+        .build()
+        .unwrap();
     dbg!(&log);
     let serde = PackageBuilder::new("serde")
         .authors(vec!["djmitche".into()])
-        .version(String::from("4.0"))
+        .version(String::from("4.0.0"))
         .dependency(base64.as_dependency())
         .dependency(log.as_dependency())
         .language(Language::Rust)
-        .build();
-    dbg!(serde);
+        .build()
+        .unwrap();
+    dbg!(&serde);
+    // the same package as a dependency tree
+    let mut registry = Registry::new();
+    for package in [base64, log, serde] {
+        registry.insert(package);
+    }
+    println!("{}", registry.tree("serde").unwrap());
 
     // =============== Smart Pointers ===============
     // 9、Box<T>
@@ -349,7 +218,21 @@ fn main() {
     println!("five: {}", five);
     // Recusive data type or data type has the dynamic size cannot be stored on the stack.
     // We can do this with Box because the size of the pointer is fixed
-    let list: List<i32> = List::Element(1, Box::new(List::Element(2, Box::new(List::Nil))));
+    let cons: Cons<i32> = Cons::Element(1, Box::new(Cons::Element(2, Box::new(Cons::Nil))));
+    println!("{cons:?}");
+    // follow the boxes to the end of the list
+    let mut rest = &cons;
+    let mut len = 0;
+    while let Cons::Element(_, tail) = rest {
+        len += 1;
+        rest = tail;
+    }
+    println!("cons list of {len} elements");
+    // List keeps its elements in the same kind of cells and hides them
+    // behind push_front, pop_front and friends
+    let mut list: List<i32> = List::new();
+    list.push_front(2);
+    list.push_front(1);
     println!("{list:?}");
     // Box can be very helpful when:
     // - have a type whose size can’t be known at compile time, but the rust complier wants to know the exact size.
@@ -0,0 +1,506 @@
+//! Lockfiles, which freeze the versions a solved dependency graph picked.
+//!
+//! A lockfile lists every package of the graph, the root included, sorted by
+//! name and version, in the format of manifests:
+//!
+//! ```toml
+//! [[package]]
+//! name = "app"
+//! version = "0.1.0"
+//! dependencies = ["log 0.4.20", "serde 1.1.0"]
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use super::Package;
+use super::manifest::{Cursor, SyntaxError, Value, quoted, write_entry};
+use super::version::Version;
+
+/// One package of a lockfile, with the exact versions of its dependencies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: Version,
+    /// Sorted by name.
+    pub dependencies: Vec<(String, Version)>,
+}
+
+/// The packages of a solved dependency graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lockfile {
+    /// Sorted by name and version.
+    packages: Vec<LockedPackage>,
+}
+
+/// A way in which a manifest and its lockfile disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockIssue {
+    /// A package is needed but the lockfile has no entry for it.
+    /// `required_by` is `None` for the root package.
+    Missing {
+        name: String,
+        required_by: Option<String>,
+    },
+    /// The locked version no longer meets the requirement on it.
+    Stale {
+        name: String,
+        locked: Version,
+        requirement: String,
+    },
+    /// The lockfile has an entry that nothing depends on anymore.
+    Unused { name: String, version: Version },
+}
+
+impl fmt::Display for LockIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockIssue::Missing {
+                name,
+                required_by: None,
+            } => write!(f, "{name} is not locked"),
+            LockIssue::Missing {
+                name,
+                required_by: Some(dependent),
+            } => write!(f, "{name} is not locked (required by {dependent})"),
+            LockIssue::Stale {
+                name,
+                locked,
+                requirement,
+            } => write!(
+                f,
+                "{name} is locked at {locked}, which does not match {requirement}"
+            ),
+            LockIssue::Unused { name, version } => write!(f, "{name} {version} is no longer used"),
+        }
+    }
+}
+
+/// Every way in which a manifest and its lockfile disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError(pub Vec<LockIssue>);
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lockfile is out of date: ")?;
+        for (i, issue) in self.0.iter().enumerate() {
+            write!(f, "{}{issue}", if i == 0 { "" } else { "; " })?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// A package that a solution was asked to lock but has no version of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsolvedError {
+    pub name: String,
+    pub required_by: String,
+}
+
+impl fmt::Display for UnsolvedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the solution has no version of {} (required by {})",
+            self.name, self.required_by
+        )
+    }
+}
+
+impl std::error::Error for UnsolvedError {}
+
+impl Lockfile {
+    /// Freeze the versions `solution` picked for the dependencies of `root`,
+    /// as returned by [`Index::solve`](super::solve::Index::solve). Fails
+    /// if the solution has no version of a package that `root` or one of the
+    /// chosen packages depends on, as when it was solved for another root.
+    pub fn new(root: &Package, solution: &BTreeMap<&str, &Package>) -> Result<Self, UnsolvedError> {
+        let mut packages = Vec::new();
+        for package in solution.values().copied().chain([root]) {
+            let mut dependencies = BTreeMap::new();
+            for dependency in &package.dependencies {
+                let locked = match solution.get(dependency.name()) {
+                    Some(locked) => *locked,
+                    None if dependency.name == root.name => root,
+                    None => {
+                        return Err(UnsolvedError {
+                            name: dependency.name.clone(),
+                            required_by: package.name.clone(),
+                        });
+                    }
+                };
+                dependencies.insert(dependency.name.clone(), locked.version.clone());
+            }
+            packages.push(LockedPackage {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                dependencies: dependencies.into_iter().collect(),
+            });
+        }
+        packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        Ok(Self { packages })
+    }
+
+    pub fn packages(&self) -> &[LockedPackage] {
+        &self.packages
+    }
+
+    pub fn get(&self, name: &str, version: &Version) -> Option<&LockedPackage> {
+        self.packages
+            .iter()
+            .find(|package| package.name == name && package.version == *version)
+    }
+
+    /// Check that the lockfile still fits `manifest`: that it locks a version
+    /// of every dependency that meets the manifest's requirement, has an entry
+    /// for every locked dependency, and holds nothing that is not needed.
+    pub fn verify(&self, manifest: &Package) -> Result<(), VerifyError> {
+        let mut issues = Vec::new();
+        let mut used = HashSet::new();
+        let Some(root) = self.packages.iter().find(|p| p.name == manifest.name) else {
+            issues.push(LockIssue::Missing {
+                name: manifest.name.clone(),
+                required_by: None,
+            });
+            return Err(VerifyError(issues));
+        };
+        used.insert((&root.name, &root.version));
+        if manifest.version != root.version {
+            issues.push(LockIssue::Stale {
+                name: root.name.clone(),
+                locked: root.version.clone(),
+                requirement: format!("={}", manifest.version),
+            });
+        }
+
+        let locked: BTreeMap<&str, &Version> = root
+            .dependencies
+            .iter()
+            .map(|(name, version)| (name.as_str(), version))
+            .collect();
+        // the dependencies the manifest still asks for, checked against the
+        // versions in the lockfile
+        let mut stack = Vec::new();
+        for dependency in &manifest.dependencies {
+            let Some(version) = locked.get(dependency.name()) else {
+                issues.push(LockIssue::Missing {
+                    name: dependency.name.clone(),
+                    required_by: Some(manifest.name.clone()),
+                });
+                continue;
+            };
+            let matches = dependency
+                .version_req()
+                .is_ok_and(|requirement| requirement.matches(version));
+            if !matches {
+                issues.push(LockIssue::Stale {
+                    name: dependency.name.clone(),
+                    locked: (*version).clone(),
+                    requirement: dependency.version_expression.clone(),
+                });
+            }
+            stack.push((&root.name, dependency.name(), *version));
+        }
+
+        // every entry reachable from the root must be in the lockfile
+        while let Some((dependent, name, version)) = stack.pop() {
+            let Some(package) = self.get(name, version) else {
+                issues.push(LockIssue::Missing {
+                    name: name.to_string(),
+                    required_by: Some(dependent.clone()),
+                });
+                continue;
+            };
+            if used.insert((&package.name, &package.version)) {
+                for (name, version) in &package.dependencies {
+                    stack.push((&package.name, name, version));
+                }
+            }
+        }
+        let mut unused: BTreeSet<(&String, &Version)> = self
+            .packages
+            .iter()
+            .map(|package| (&package.name, &package.version))
+            .collect();
+        unused.retain(|entry| !used.contains(entry));
+        issues.extend(unused.into_iter().map(|(name, version)| LockIssue::Unused {
+            name: name.clone(),
+            version: version.clone(),
+        }));
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(VerifyError(issues))
+        }
+    }
+}
+
+impl fmt::Display for Lockfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        out.push_str("# This file is generated. Do not edit it by hand.\n");
+        for package in &self.packages {
+            out.push_str("\n[[package]]\n");
+            write_entry(&mut out, "name", &quoted(&package.name));
+            write_entry(&mut out, "version", &quoted(&package.version.to_string()));
+            if !package.dependencies.is_empty() {
+                let dependencies: Vec<String> = package
+                    .dependencies
+                    .iter()
+                    .map(|(name, version)| quoted(&format!("{name} {version}")))
+                    .collect();
+                write_entry(
+                    &mut out,
+                    "dependencies",
+                    &format!("[{}]", dependencies.join(", ")),
+                );
+            }
+        }
+        f.write_str(&out)
+    }
+}
+
+/// A `[[package]]` entry while it is being read.
+struct Entry {
+    header: Cursor,
+    name: Option<String>,
+    version: Option<Version>,
+    dependencies: Vec<(String, Version)>,
+    keys: HashSet<String>,
+}
+
+impl Entry {
+    fn finish(self) -> Result<LockedPackage, SyntaxError> {
+        let missing = |key| {
+            self.header
+                .error_at(0, format!("package is missing `{key}`"))
+        };
+        let name = self.name.clone().ok_or_else(|| missing("name"))?;
+        let version = self.version.clone().ok_or_else(|| missing("version"))?;
+        let mut dependencies = self.dependencies;
+        dependencies.sort();
+        Ok(LockedPackage {
+            name,
+            version,
+            dependencies,
+        })
+    }
+}
+
+impl FromStr for Lockfile {
+    type Err = SyntaxError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut entries: Vec<Entry> = Vec::new();
+        for (i, text) in input.lines().enumerate() {
+            let mut cursor = Cursor::new(i + 1, text);
+            if cursor.at_end() {
+                continue;
+            }
+            let start = cursor.pos;
+            if cursor.peek() == Some('[') {
+                cursor.expect('[')?;
+                cursor.expect('[')?;
+                cursor.skip_whitespace();
+                if cursor.key()? != "package" {
+                    return Err(cursor.error_at(start, "expected [[package]]"));
+                }
+                cursor.skip_whitespace();
+                cursor.expect(']')?;
+                cursor.expect(']')?;
+                cursor.expect_end()?;
+                entries.push(Entry {
+                    header: cursor,
+                    name: None,
+                    version: None,
+                    dependencies: Vec::new(),
+                    keys: HashSet::new(),
+                });
+                continue;
+            }
+
+            let (key, value_start, value) = cursor.entry()?;
+            let Some(entry) = entries.last_mut() else {
+                return Err(cursor.error_at(start, "key outside of a [[package]]"));
+            };
+            if !entry.keys.insert(key.clone()) {
+                return Err(cursor.error_at(start, format!("duplicate key `{key}`")));
+            }
+            let invalid = |what: &str| cursor.error_at(value_start, format!("invalid {what}"));
+            match (key.as_str(), value) {
+                ("name", Value::String(name)) => entry.name = Some(name),
+                ("version", Value::String(version)) => {
+                    entry.version = Some(version.parse().map_err(|_| invalid("version"))?);
+                }
+                ("dependencies", Value::Array(dependencies)) => {
+                    for dependency in dependencies {
+                        let (name, version) = dependency
+                            .rsplit_once(' ')
+                            .ok_or_else(|| invalid("dependency"))?;
+                        let version = version.parse().map_err(|_| invalid("dependency"))?;
+                        entry.dependencies.push((name.to_string(), version));
+                    }
+                }
+                ("name" | "version" | "dependencies", _) => return Err(invalid(&key)),
+                _ => return Err(cursor.error_at(start, format!("unknown key `{key}`"))),
+            }
+        }
+
+        let mut packages = Vec::new();
+        let mut seen = HashSet::new();
+        for entry in entries {
+            let header = entry.header.error_at(0, "duplicate package");
+            let package = entry.finish()?;
+            if !seen.insert((package.name.clone(), package.version.clone())) {
+                return Err(header);
+            }
+            packages.push(package);
+        }
+        packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        Ok(Self { packages })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::solve::Index;
+    use crate::package::tests::package;
+
+    fn index() -> Index {
+        let mut index = Index::new();
+        for package in [
+            package("log", "0.4.0", &[]),
+            package("log", "0.4.20", &[]),
+            package("serde", "1.1.0", &[("log", "^0.4"), ("base64", "^0.13")]),
+            package("base64", "0.13.1", &[]),
+            package("rand", "0.8.5", &[]),
+        ] {
+            index.insert(package);
+        }
+        index
+    }
+
+    const LOCKFILE: &str = r#"# This file is generated. Do not edit it by hand.
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["log 0.4.20", "serde 1.1.0"]
+
+[[package]]
+name = "base64"
+version = "0.13.1"
+
+[[package]]
+name = "log"
+version = "0.4.20"
+
+[[package]]
+name = "serde"
+version = "1.1.0"
+dependencies = ["base64 0.13.1", "log 0.4.20"]
+"#;
+
+    #[test]
+    fn write_and_read_back() {
+        let index = index();
+        let app = package("app", "0.1.0", &[("serde", "1"), ("log", "0.4")]);
+        let lockfile = Lockfile::new(&app, &index.solve(&app).unwrap()).unwrap();
+        assert_eq!(lockfile.to_string(), LOCKFILE);
+        assert_eq!(LOCKFILE.parse::<Lockfile>().unwrap(), lockfile);
+        assert_eq!(lockfile.verify(&app), Ok(()));
+
+        // a solution for another root does not cover every dependency
+        let other = package("other", "0.1.0", &[("log", "0.4")]);
+        assert_eq!(
+            Lockfile::new(&app, &index.solve(&other).unwrap()),
+            Err(UnsolvedError {
+                name: "serde".into(),
+                required_by: "app".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn verify_reports_every_issue() {
+        let lockfile: Lockfile = LOCKFILE.parse().unwrap();
+        // serde was dropped, log's requirement was raised and rand was added
+        let app = package("app", "0.1.0", &[("log", "^0.5"), ("rand", "0.8")]);
+        let err = lockfile.verify(&app).unwrap_err();
+        assert_eq!(
+            err.0,
+            [
+                LockIssue::Stale {
+                    name: "log".into(),
+                    locked: "0.4.20".parse().unwrap(),
+                    requirement: "^0.5".into(),
+                },
+                LockIssue::Missing {
+                    name: "rand".into(),
+                    required_by: Some("app".into()),
+                },
+                LockIssue::Unused {
+                    name: "base64".into(),
+                    version: "0.13.1".parse().unwrap(),
+                },
+                LockIssue::Unused {
+                    name: "serde".into(),
+                    version: "1.1.0".parse().unwrap(),
+                },
+            ]
+        );
+
+        // an entry the root depends on is gone
+        let broken: Lockfile = LOCKFILE
+            .replace("[[package]]\nname = \"base64\"\nversion = \"0.13.1\"\n", "")
+            .parse()
+            .unwrap();
+        let app = package("app", "0.1.0", &[("serde", "1"), ("log", "0.4")]);
+        assert_eq!(
+            broken.verify(&app).unwrap_err().to_string(),
+            "lockfile is out of date: base64 is not locked (required by serde)"
+        );
+    }
+
+    #[test]
+    fn reports_positions() {
+        for (input, line, column, message) in [
+            ("name = \"x\"", 1, 1, "key outside of a [[package]]"),
+            ("[package]", 1, 2, "expected `[`"),
+            ("[[crate]]", 1, 1, "expected [[package]]"),
+            ("[[package]]\nversion = \"1\"", 2, 11, "invalid version"),
+            (
+                "[[package]]\ndependencies = [\"log\"]",
+                2,
+                16,
+                "invalid dependency",
+            ),
+            (
+                "[[package]]\nname = \"a\"\nname = \"b\"",
+                3,
+                1,
+                "duplicate key `name`",
+            ),
+            (
+                "\n[[package]]\nname = \"a\"",
+                2,
+                1,
+                "package is missing `version`",
+            ),
+        ] {
+            let err = input.parse::<Lockfile>().unwrap_err();
+            assert_eq!((err.line, err.column), (line, column), "{input}");
+            assert_eq!(err.message, message);
+        }
+        let twice = "[[package]]\nname = \"a\"\nversion = \"1.0.0\"\n".repeat(2);
+        assert_eq!(
+            twice.parse::<Lockfile>().unwrap_err().to_string(),
+            "4:1: duplicate package"
+        );
+    }
+}
@@ -0,0 +1,524 @@
+//! Manifest files, written in a small subset of TOML:
+//!
+//! ```toml
+//! [package]
+//! name = "serde"
+//! version = "4.0.0"
+//! authors = ["djmitche"]
+//! language = "rust"
+//!
+//! [dependencies]
+//! base64 = "^0.13.0"
+//! ```
+//!
+//! Values are basic strings or single-line arrays of them. Comments, blank
+//! lines and quoted keys are allowed.
+
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+
+use super::{Dependency, Language, Package, PackageBuilder, PackageError};
+
+/// Where and how a file breaks the format. Lines and columns count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Why a manifest could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestError {
+    /// The file is not in the manifest format.
+    Syntax(SyntaxError),
+    /// The file is well-formed but describes an invalid package.
+    Invalid(PackageError),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Syntax(err) => write!(f, "{err}"),
+            ManifestError::Invalid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ManifestError::Syntax(err) => Some(err),
+            ManifestError::Invalid(err) => Some(err),
+        }
+    }
+}
+
+impl From<SyntaxError> for ManifestError {
+    fn from(err: SyntaxError) -> Self {
+        ManifestError::Syntax(err)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Table {
+    Package,
+    Dependencies,
+}
+
+pub(super) enum Value {
+    String(String),
+    Array(Vec<String>),
+}
+
+/// How a language is written in files.
+pub(super) fn language_name(language: Language) -> &'static str {
+    match language {
+        Language::Rust => "rust",
+        Language::Java => "java",
+        Language::Perl => "perl",
+    }
+}
+
+pub(super) fn language_from_name(name: &str) -> Option<Language> {
+    [Language::Rust, Language::Java, Language::Perl]
+        .into_iter()
+        .find(|language| language_name(*language) == name)
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// A position in one line of a file in the manifest format.
+pub(super) struct Cursor {
+    line: usize,
+    chars: Vec<char>,
+    pub(super) pos: usize,
+}
+
+impl Cursor {
+    pub(super) fn new(line: usize, text: &str) -> Self {
+        Self {
+            line,
+            chars: text.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    pub(super) fn error_at(&self, pos: usize, message: impl Into<String>) -> SyntaxError {
+        SyntaxError {
+            line: self.line,
+            column: pos + 1,
+            message: message.into(),
+        }
+    }
+
+    pub(super) fn error(&self, message: impl Into<String>) -> SyntaxError {
+        self.error_at(self.pos, message)
+    }
+
+    pub(super) fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    pub(super) fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    pub(super) fn expect(&mut self, expected: char) -> Result<(), SyntaxError> {
+        if self.peek() != Some(expected) {
+            return Err(self.error(format!("expected `{expected}`")));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Whether only whitespace and maybe a comment are left.
+    pub(super) fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        matches!(self.peek(), None | Some('#'))
+    }
+
+    pub(super) fn expect_end(&mut self) -> Result<(), SyntaxError> {
+        if !self.at_end() {
+            return Err(self.error("expected the end of the line"));
+        }
+        Ok(())
+    }
+
+    pub(super) fn key(&mut self) -> Result<String, SyntaxError> {
+        if self.peek() == Some('"') {
+            return self.string();
+        }
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("expected a key"));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    pub(super) fn string(&mut self) -> Result<String, SyntaxError> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok(value),
+                '\\' => value.push(self.escape()?),
+                c if c.is_control() && c != '\t' => {
+                    return Err(self.error_at(self.pos - 1, "control character in string"));
+                }
+                c => value.push(c),
+            }
+        }
+    }
+
+    /// The character for the escape sequence after a backslash.
+    fn escape(&mut self) -> Result<char, SyntaxError> {
+        let start = self.pos - 1;
+        let invalid = |cursor: &Self| cursor.error_at(start, "invalid escape sequence");
+        let c = self.peek().ok_or_else(|| invalid(self))?;
+        self.pos += 1;
+        let digits = match c {
+            '"' => return Ok('"'),
+            '\\' => return Ok('\\'),
+            'n' => return Ok('\n'),
+            't' => return Ok('\t'),
+            'r' => return Ok('\r'),
+            'u' => 4,
+            'U' => 8,
+            _ => return Err(invalid(self)),
+        };
+        let hex: String = self.chars.iter().skip(self.pos).take(digits).collect();
+        self.pos += digits;
+        if hex.len() != digits {
+            return Err(invalid(self));
+        }
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| invalid(self))
+    }
+
+    /// A `key = value` line, with the column the value starts at.
+    pub(super) fn entry(&mut self) -> Result<(String, usize, Value), SyntaxError> {
+        let key = self.key()?;
+        self.skip_whitespace();
+        self.expect('=')?;
+        self.skip_whitespace();
+        let value_start = self.pos;
+        let value = self.value()?;
+        self.expect_end()?;
+        Ok((key, value_start, value))
+    }
+
+    pub(super) fn value(&mut self) -> Result<Value, SyntaxError> {
+        match self.peek() {
+            Some('"') => self.string().map(Value::String),
+            Some('[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        return Ok(Value::Array(values));
+                    }
+                    values.push(self.string()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {}
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+            }
+            _ => Err(self.error("expected a string or an array")),
+        }
+    }
+}
+
+impl Package {
+    /// Read a package from the text of a manifest.
+    pub fn from_manifest(input: &str) -> Result<Package, ManifestError> {
+        let mut package = PackageBuilder::new("");
+        let mut table = None;
+        let mut seen_tables = HashSet::new();
+        let mut seen_keys = HashSet::new();
+        for (i, text) in input.lines().enumerate() {
+            let mut cursor = Cursor::new(i + 1, text);
+            if cursor.at_end() {
+                continue;
+            }
+            let start = cursor.pos;
+            if cursor.peek() == Some('[') {
+                cursor.pos += 1;
+                cursor.skip_whitespace();
+                let name = cursor.key()?;
+                cursor.skip_whitespace();
+                cursor.expect(']')?;
+                cursor.expect_end()?;
+                table = Some(match name.as_str() {
+                    "package" => Table::Package,
+                    "dependencies" => Table::Dependencies,
+                    _ => {
+                        return Err(cursor
+                            .error_at(start, format!("unknown table [{name}]"))
+                            .into());
+                    }
+                });
+                if !seen_tables.insert(name.clone()) {
+                    return Err(cursor
+                        .error_at(start, format!("duplicate table [{name}]"))
+                        .into());
+                }
+                continue;
+            }
+
+            let (key, value_start, value) = cursor.entry()?;
+            let Some(table) = table else {
+                return Err(cursor.error_at(start, "key outside of a table").into());
+            };
+            if !seen_keys.insert((table, key.clone())) {
+                return Err(cursor
+                    .error_at(start, format!("duplicate key `{key}`"))
+                    .into());
+            }
+            let expected = |what: &str| cursor.error_at(value_start, format!("expected {what}"));
+            match (table, key.as_str(), value) {
+                (Table::Package, "name", Value::String(name)) => package.name = name,
+                (Table::Package, "version", Value::String(version)) => package.version = version,
+                (Table::Package, "authors", Value::Array(authors)) => package.authors = authors,
+                (Table::Package, "language", Value::String(language)) => {
+                    package.language = Some(
+                        language_from_name(&language)
+                            .ok_or_else(|| expected("one of \"rust\", \"java\" or \"perl\""))?,
+                    );
+                }
+                (Table::Package, "name" | "version" | "language", _) => {
+                    return Err(expected("a string").into());
+                }
+                (Table::Package, "authors", _) => return Err(expected("an array").into()),
+                (Table::Package, _, _) => {
+                    return Err(cursor
+                        .error_at(start, format!("unknown key `{key}`"))
+                        .into());
+                }
+                (Table::Dependencies, _, Value::String(requirement)) => {
+                    package.dependencies.push(Dependency::new(key, requirement));
+                }
+                (Table::Dependencies, _, Value::Array(_)) => {
+                    return Err(expected("a string").into());
+                }
+            }
+        }
+        // validate the package the way `PackageBuilder` does
+        package.build().map_err(ManifestError::Invalid)
+    }
+
+    /// The manifest text for this package, which
+    /// [`from_manifest`](Package::from_manifest) reads back unchanged.
+    pub fn to_manifest(&self) -> String {
+        let mut out = String::new();
+        out.push_str("[package]\n");
+        write_entry(&mut out, "name", &quoted(&self.name));
+        write_entry(&mut out, "version", &quoted(&self.version.to_string()));
+        if !self.authors.is_empty() {
+            let authors: Vec<String> = self.authors.iter().map(|a| quoted(a)).collect();
+            write_entry(&mut out, "authors", &format!("[{}]", authors.join(", ")));
+        }
+        if let Some(language) = self.language {
+            write_entry(&mut out, "language", &quoted(language_name(language)));
+        }
+        if !self.dependencies.is_empty() {
+            out.push_str("\n[dependencies]\n");
+            for dependency in &self.dependencies {
+                write_entry(
+                    &mut out,
+                    &dependency.name,
+                    &quoted(&dependency.version_expression),
+                );
+            }
+        }
+        out
+    }
+}
+
+pub(super) fn write_entry(out: &mut String, key: &str, value: &str) {
+    if is_bare_key(key) {
+        out.push_str(key);
+    } else {
+        out.push_str(&quoted(key));
+    }
+    out.push_str(" = ");
+    out.push_str(value);
+    out.push('\n');
+}
+
+pub(super) fn quoted(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                write!(out, "\\u{:04X}", c as u32).expect("writing to a String cannot fail");
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syntax_error(input: &str) -> (usize, usize, String) {
+        match Package::from_manifest(input) {
+            Err(ManifestError::Syntax(SyntaxError {
+                line,
+                column,
+                message,
+            })) => (line, column, message),
+            other => panic!("expected a syntax error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_manifest() {
+        let input = r#"
+# the serde package
+[package]
+name = "serde"
+version = "4.0.0"   # latest
+authors = [ "djmitche", "Jane \"JD\" Doeé" ]
+language = "rust"
+
+[dependencies]
+base64 = "^0.13.0"
+"log" = ">=0.4, <0.5"
+"#;
+        let package = Package::from_manifest(input).unwrap();
+        let expected = PackageBuilder::new("serde")
+            .version("4.0.0")
+            .authors(vec!["djmitche".into(), "Jane \"JD\" Doe\u{e9}".into()])
+            .language(Language::Rust)
+            .dependency(Dependency::new("base64", "^0.13.0"))
+            .dependency(Dependency::new("log", ">=0.4, <0.5"))
+            .build()
+            .unwrap();
+        assert_eq!(package, expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let package = PackageBuilder::new("odd-name_1")
+            .version("1.0.0-rc.1+build")
+            .authors(vec!["tab\there".into(), "back\\slash \u{7}".into()])
+            .language(Language::Perl)
+            .dependency(Dependency::new("with space", "*"))
+            .dependency(Dependency::new("log", "~0.4"))
+            .build()
+            .unwrap();
+        let manifest = package.to_manifest();
+        assert_eq!(Package::from_manifest(&manifest).unwrap(), package);
+
+        let plain = PackageBuilder::new("log").version("0.4.0").build().unwrap();
+        assert_eq!(
+            plain.to_manifest(),
+            "[package]\nname = \"log\"\nversion = \"0.4.0\"\n"
+        );
+        assert_eq!(Package::from_manifest(&plain.to_manifest()).unwrap(), plain);
+    }
+
+    #[test]
+    fn reports_positions() {
+        for (input, line, column, message) in [
+            ("name = \"x\"", 1, 1, "key outside of a table"),
+            ("[package]\nname = x", 2, 8, "expected a string or an array"),
+            ("[package]\nname \"x\"", 2, 6, "expected `=`"),
+            ("[package]\nname = \"x", 2, 10, "unterminated string"),
+            (
+                "[package]\nname = \"a\\qb\"",
+                2,
+                10,
+                "invalid escape sequence",
+            ),
+            (
+                "[package]\nname = \"x\" y",
+                2,
+                12,
+                "expected the end of the line",
+            ),
+            (
+                "[package]\nauthors = [\"a\" \"b\"]",
+                2,
+                16,
+                "expected `,` or `]`",
+            ),
+            ("[package]\nauthors = \"a\"", 2, 11, "expected an array"),
+            (
+                "[package]\nname = \"a\"\nname = \"b\"",
+                3,
+                1,
+                "duplicate key `name`",
+            ),
+            (
+                "[package]\nhomepage = \"x\"",
+                2,
+                1,
+                "unknown key `homepage`",
+            ),
+            ("[package]\nlanguage = \"go\"", 2, 12, "expected one of"),
+            ("\n  [features]", 2, 3, "unknown table [features]"),
+            ("[package]\n[package]", 2, 1, "duplicate table [package]"),
+        ] {
+            let (actual_line, actual_column, actual_message) = syntax_error(input);
+            assert_eq!((actual_line, actual_column), (line, column), "{input}");
+            assert!(actual_message.starts_with(message), "{actual_message}");
+        }
+        assert_eq!(
+            Package::from_manifest("[package]\nname = \"x\" y")
+                .unwrap_err()
+                .to_string(),
+            "2:12: expected the end of the line"
+        );
+    }
+
+    #[test]
+    fn reports_invalid_packages() {
+        let err = Package::from_manifest("[package]\nname = \"x\"\n[dependencies]\nx = \"1\"");
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "invalid package: version is empty; depends on itself"
+        );
+    }
+}
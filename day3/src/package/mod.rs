@@ -0,0 +1,390 @@
+pub mod lock;
+pub mod manifest;
+pub mod render;
+pub mod resolve;
+pub mod solve;
+pub mod store;
+pub mod version;
+
+use std::fmt;
+
+use version::{Version, VersionError, VersionReq};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Rust,
+    Java,
+    Perl,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    name: String,
+    version_expression: String,
+}
+
+impl Dependency {
+    pub fn new(name: impl Into<String>, version_expression: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version_expression: version_expression.into(),
+        }
+    }
+
+    /// The name of the package depended on.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version_expression(&self) -> &str {
+        &self.version_expression
+    }
+
+    /// The version expression, parsed.
+    pub fn version_req(&self) -> Result<VersionReq, VersionError> {
+        self.version_expression.parse()
+    }
+}
+
+/// A representation of a software package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    name: String,
+    version: Version,
+    authors: Vec<String>,
+    dependencies: Vec<Dependency>,
+    language: Option<Language>,
+}
+
+impl Package {
+    /// Return a representation of this package as a dependency, for use in
+    /// building other packages. The dependency accepts any version that is
+    /// semver-compatible with this one.
+    pub fn as_dependency(&self) -> Dependency {
+        Dependency {
+            name: self.name.clone(),
+            version_expression: format!("^{}", self.version),
+        }
+    }
+
+    /// Whether the version of this package meets `requirement`.
+    pub fn matches(&self, requirement: &VersionReq) -> bool {
+        requirement.matches(&self.version)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    pub fn authors(&self) -> &[String] {
+        &self.authors
+    }
+
+    pub fn dependencies(&self) -> &[Dependency] {
+        &self.dependencies
+    }
+
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+}
+
+/// A rule that a package breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    EmptyName,
+    EmptyVersion,
+    /// The version is not a semantic version.
+    InvalidVersion(VersionError),
+    /// The package depends on the named package more than once.
+    DuplicateDependency(String),
+    /// The package depends on itself.
+    SelfDependency,
+    /// A dependency has an empty name.
+    EmptyDependencyName,
+    /// The version expression of a dependency does not parse.
+    InvalidRequirement {
+        dependency: String,
+        error: VersionError,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::EmptyName => write!(f, "name is empty"),
+            Violation::EmptyVersion => write!(f, "version is empty"),
+            Violation::InvalidVersion(err) => write!(f, "version is invalid: {err}"),
+            Violation::DuplicateDependency(name) => {
+                write!(f, "depends on {name} more than once")
+            }
+            Violation::SelfDependency => write!(f, "depends on itself"),
+            Violation::EmptyDependencyName => write!(f, "dependency name is empty"),
+            Violation::InvalidRequirement { dependency, error } => {
+                write!(f, "requirement on {dependency} is invalid: {error}")
+            }
+        }
+    }
+}
+
+/// Every rule a package breaks, in the order they were found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageError(pub Vec<Violation>);
+
+impl fmt::Display for PackageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid package: ")?;
+        for (i, violation) in self.0.iter().enumerate() {
+            write!(f, "{}{violation}", if i == 0 { "" } else { "; " })?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PackageError {}
+
+fn parse_version(version: &str) -> Result<Version, Violation> {
+    if version.is_empty() {
+        return Err(Violation::EmptyVersion);
+    }
+    version.parse().map_err(Violation::InvalidVersion)
+}
+
+/// The rules broken by adding `dependency` to a package called `name` that
+/// already has the dependencies `earlier`.
+fn check_dependency(name: &str, earlier: &[Dependency], dependency: &Dependency) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    if dependency.name.is_empty() {
+        violations.push(Violation::EmptyDependencyName);
+    } else if dependency.name == name {
+        violations.push(Violation::SelfDependency);
+    }
+    if earlier.iter().any(|other| other.name == dependency.name) {
+        violations.push(Violation::DuplicateDependency(dependency.name.clone()));
+    }
+    if let Err(error) = dependency.version_req() {
+        violations.push(Violation::InvalidRequirement {
+            dependency: dependency.name.clone(),
+            error,
+        });
+    }
+    violations
+}
+
+/// The parts of a [`Package`], checked and put together by
+/// [`build`](PackageBuilder::build). The version is kept as given until then.
+#[derive(Debug)]
+pub struct PackageBuilder {
+    name: String,
+    version: String,
+    authors: Vec<String>,
+    dependencies: Vec<Dependency>,
+    language: Option<Language>,
+}
+
+impl PackageBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: String::new(),
+            authors: Vec::new(),
+            dependencies: Vec::new(),
+            language: None,
+        }
+    }
+
+    /// Set the package version.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Set the package version, failing right away if it is not a valid
+    /// semantic version. On failure the builder is handed back unchanged,
+    /// boxed to keep the error small, along with the error.
+    pub fn try_version(
+        self,
+        version: impl Into<String>,
+    ) -> Result<Self, (Box<Self>, PackageError)> {
+        let version = version.into();
+        match parse_version(&version) {
+            Err(violation) => Err((Box::new(self), PackageError(vec![violation]))),
+            Ok(_) => Ok(self.version(version)),
+        }
+    }
+
+    /// Set the package authors.
+    pub fn authors(mut self, authors: Vec<String>) -> Self {
+        self.authors = authors;
+        self
+    }
+
+    /// Add an additional dependency.
+    pub fn dependency(mut self, dependency: Dependency) -> Self {
+        self.dependencies.push(dependency);
+        self
+    }
+
+    /// Add an additional dependency, failing right away if the package
+    /// cannot have it. On failure the builder is handed back unchanged,
+    /// boxed to keep the error small, along with the error.
+    pub fn try_dependency(self, dependency: Dependency) -> Result<Self, (Box<Self>, PackageError)> {
+        let violations = check_dependency(&self.name, &self.dependencies, &dependency);
+        if !violations.is_empty() {
+            return Err((Box::new(self), PackageError(violations)));
+        }
+        Ok(self.dependency(dependency))
+    }
+
+    /// Set the language. If not set, language defaults to None.
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Finish the package, or report every rule it breaks.
+    pub fn build(self) -> Result<Package, PackageError> {
+        let mut violations = Vec::new();
+        if self.name.is_empty() {
+            violations.push(Violation::EmptyName);
+        }
+        let version = match parse_version(&self.version) {
+            Ok(version) => Some(version),
+            Err(violation) => {
+                violations.push(violation);
+                None
+            }
+        };
+        for (i, dependency) in self.dependencies.iter().enumerate() {
+            let earlier = &self.dependencies[..i];
+            for violation in check_dependency(&self.name, earlier, dependency) {
+                // a third copy of a dependency is not a new problem
+                if !violations.contains(&violation) {
+                    violations.push(violation);
+                }
+            }
+        }
+        match version {
+            Some(version) if violations.is_empty() => Ok(Package {
+                name: self.name,
+                version,
+                authors: self.authors,
+                dependencies: self.dependencies,
+                language: self.language,
+            }),
+            _ => Err(PackageError(violations)),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A package with the given dependencies, built and validated. Shared by
+    /// the tests of the other package modules.
+    pub(crate) fn package(name: &str, version: &str, dependencies: &[(&str, &str)]) -> Package {
+        let mut builder = PackageBuilder::new(name).version(version);
+        for (name, requirement) in dependencies {
+            builder = builder.dependency(Dependency::new(*name, *requirement));
+        }
+        builder.build().unwrap()
+    }
+
+    /// A package that skips the checks of [`PackageBuilder::build`], for
+    /// testing code that has to cope with packages it would reject. Only the
+    /// version has to be valid.
+    pub(crate) fn unvalidated(name: &str, version: &str, dependencies: &[(&str, &str)]) -> Package {
+        Package {
+            name: name.to_string(),
+            version: version.parse().unwrap(),
+            authors: Vec::new(),
+            dependencies: dependencies
+                .iter()
+                .map(|(name, requirement)| Dependency::new(*name, *requirement))
+                .collect(),
+            language: None,
+        }
+    }
+
+    #[test]
+    fn dependencies_accept_compatible_versions() {
+        let log = PackageBuilder::new("log")
+            .version("0.4.20")
+            .build()
+            .unwrap();
+        let dependency = log.as_dependency();
+        assert_eq!(dependency.version_expression(), "^0.4.20");
+        let requirement = dependency.version_req().unwrap();
+        assert!(log.matches(&requirement));
+        for (version, matches) in [("0.4.21", true), ("0.5.0", false), ("0.4.2", false)] {
+            let other = PackageBuilder::new("log").version(version).build().unwrap();
+            assert_eq!(other.matches(&requirement), matches, "{version}");
+        }
+    }
+
+    #[test]
+    fn build_reports_every_violation() {
+        let err = PackageBuilder::new("")
+            .dependency(Dependency::new("log", "^0.4"))
+            .dependency(Dependency::new("", "1"))
+            .dependency(Dependency::new("log", "0.4"))
+            .dependency(Dependency::new("log", "0.4"))
+            .dependency(Dependency::new("rand", "one"))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.0,
+            [
+                Violation::EmptyName,
+                Violation::EmptyVersion,
+                Violation::EmptyDependencyName,
+                Violation::DuplicateDependency("log".into()),
+                Violation::InvalidRequirement {
+                    dependency: "rand".into(),
+                    error: VersionError::InvalidNumber("one".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            PackageBuilder::new("log")
+                .version("0.4")
+                .build()
+                .unwrap_err()
+                .to_string(),
+            "invalid package: version is invalid: version 0.4 has no patch number"
+        );
+        assert_eq!(
+            PackageBuilder::new("app")
+                .version("1.0.0")
+                .dependency(Dependency::new("", "1"))
+                .build()
+                .unwrap_err(),
+            PackageError(vec![Violation::EmptyDependencyName])
+        );
+    }
+
+    #[test]
+    fn try_setters_fail_early() {
+        let builder = PackageBuilder::new("serde").try_version("1.0.0").unwrap();
+        let (_, err) = PackageBuilder::new("serde").try_version("").unwrap_err();
+        assert_eq!(err, PackageError(vec![Violation::EmptyVersion]));
+        let builder = builder
+            .try_dependency(Dependency::new("log", "^0.4"))
+            .unwrap();
+        let (builder, err) = builder
+            .try_dependency(Dependency::new("serde", "1"))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "invalid package: depends on itself");
+
+        // the failed setter left the rest of the package alone
+        let (builder, _) = builder.try_version("one").unwrap_err();
+        let serde = builder.build().unwrap();
+        assert_eq!(serde.version().to_string(), "1.0.0");
+        assert_eq!(serde.dependencies(), [Dependency::new("log", "^0.4")]);
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+
+use super::Package;
+use super::resolve::Registry;
+
+/// A package and its transitive dependencies, drawn like `cargo tree`:
+///
+/// ```text
+/// app v0.1.0
+/// |-- log v0.4.20
+/// `-- serde v1.1.0
+///     |-- base64 v0.13.1
+///     `-- log v0.4.20
+/// ```
+///
+/// A package whose dependencies were already drawn higher up is marked with
+/// `(*)` instead of being expanded again. Created by [`Registry::tree`].
+pub struct Tree<'a> {
+    registry: &'a Registry,
+    root: &'a Package,
+    invert: bool,
+    depth: Option<usize>,
+}
+
+/// A line of the tree.
+enum Label<'a> {
+    Package(&'a Package),
+    /// A dependency that is not in the registry.
+    Missing(&'a str),
+}
+
+impl Registry {
+    /// The dependency tree of the package `root`, or `None` if the registry
+    /// does not have it.
+    pub fn tree(&self, root: &str) -> Option<Tree<'_>> {
+        Some(Tree {
+            registry: self,
+            root: self.get(root)?,
+            invert: false,
+            depth: None,
+        })
+    }
+}
+
+impl<'a> Tree<'a> {
+    /// Show the packages that depend on the root, transitively, instead of
+    /// the ones it depends on.
+    pub fn invert(mut self) -> Self {
+        self.invert = true;
+        self
+    }
+
+    /// Only draw packages up to `depth` levels below the root.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// The packages below `package`, sorted by name.
+    fn children(&self, package: &'a Package) -> Vec<Label<'a>> {
+        if self.invert {
+            return self
+                .registry
+                .packages()
+                .filter(|dependent| {
+                    dependent
+                        .dependencies
+                        .iter()
+                        .any(|dependency| dependency.name == package.name)
+                })
+                .map(Label::Package)
+                .collect();
+        }
+        let mut names: Vec<&str> = package
+            .dependencies
+            .iter()
+            .map(|dependency| dependency.name())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .map(|name| match self.registry.get(name) {
+                Some(dependency) => Label::Package(dependency),
+                None => Label::Missing(name),
+            })
+            .collect()
+    }
+}
+
+impl Display for Tree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut expanded = HashSet::new();
+        // each line with the prefix of its parent and whether it is the last
+        // child; `None` for the root
+        let mut stack = vec![(Label::Package(self.root), String::new(), None, 0)];
+        while let Some((label, prefix, last, depth)) = stack.pop() {
+            let connector = match last {
+                None => "",
+                Some(false) => "|-- ",
+                Some(true) => "`-- ",
+            };
+            let package = match label {
+                Label::Missing(name) => {
+                    writeln!(f, "{prefix}{connector}{name} (missing)")?;
+                    continue;
+                }
+                Label::Package(package) => package,
+            };
+            write!(
+                f,
+                "{prefix}{connector}{} v{}",
+                package.name, package.version
+            )?;
+            let children = self.children(package);
+            if self.depth.is_some_and(|limit| depth >= limit) || children.is_empty() {
+                writeln!(f)?;
+                continue;
+            }
+            if !expanded.insert(&package.name) {
+                writeln!(f, " (*)")?;
+                continue;
+            }
+            writeln!(f)?;
+            let prefix = match last {
+                None => prefix,
+                Some(false) => prefix + "|   ",
+                Some(true) => prefix + "    ",
+            };
+            let count = children.len();
+            // push in reverse so that the first child is drawn first
+            for (i, child) in children.into_iter().enumerate().rev() {
+                stack.push((child, prefix.clone(), Some(i + 1 == count), depth + 1));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::tests::package;
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        for package in [
+            package(
+                "app",
+                "0.1.0",
+                &[("serde", "*"), ("log", "*"), ("rand", "*")],
+            ),
+            package("serde", "1.1.0", &[("log", "*"), ("base64", "*")]),
+            package("log", "0.4.20", &[("cfg-if", "*")]),
+            package("cfg-if", "1.0.0", &[]),
+            package("base64", "0.13.1", &[]),
+        ] {
+            registry.insert(package);
+        }
+        registry
+    }
+
+    #[test]
+    fn marks_repeated_subtrees() {
+        let registry = registry();
+        assert_eq!(
+            registry.tree("app").unwrap().to_string(),
+            "\
+app v0.1.0
+|-- log v0.4.20
+|   `-- cfg-if v1.0.0
+|-- rand (missing)
+`-- serde v1.1.0
+    |-- base64 v0.13.1
+    `-- log v0.4.20 (*)
+"
+        );
+        assert!(registry.tree("tokio").is_none());
+    }
+
+    #[test]
+    fn depth_limit() {
+        assert_eq!(
+            registry().tree("app").unwrap().depth(1).to_string(),
+            "\
+app v0.1.0
+|-- log v0.4.20
+|-- rand (missing)
+`-- serde v1.1.0
+"
+        );
+        assert_eq!(
+            registry().tree("app").unwrap().depth(0).to_string(),
+            "app v0.1.0\n"
+        );
+    }
+
+    #[test]
+    fn inverted() {
+        assert_eq!(
+            registry().tree("cfg-if").unwrap().invert().to_string(),
+            "\
+cfg-if v1.0.0
+`-- log v0.4.20
+    |-- app v0.1.0
+    `-- serde v1.1.0
+        `-- app v0.1.0
+"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use super::Package;
+
+/// A set of packages, looked up by name when resolving dependencies.
+///
+/// Only the names of dependencies are followed; version expressions are not
+/// checked here.
+#[derive(Debug, Default)]
+pub struct Registry {
+    packages: BTreeMap<String, Package>,
+}
+
+/// A package that is depended on but not in the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Missing {
+    pub name: String,
+    /// The packages that depend on it, sorted, or empty if it was asked for
+    /// directly.
+    pub required_by: Vec<String>,
+}
+
+/// Why dependencies could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// Every package that is needed but not in the registry.
+    Missing(Vec<Missing>),
+    /// Packages that depend on each other in a circle. The path starts and
+    /// ends with the same package.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::Missing(missing) => {
+                write!(f, "missing packages: ")?;
+                for (i, package) in missing.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match package.required_by.as_slice() {
+                        [] => write!(f, "{} (requested)", package.name)?,
+                        required_by => write!(
+                            f,
+                            "{} (required by {})",
+                            package.name,
+                            required_by.join(", ")
+                        )?,
+                    }
+                }
+                Ok(())
+            }
+            ResolveError::Cycle(path) => write!(f, "dependency cycle: {}", path.join(" -> ")),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Visiting,
+    Done,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a package, returning the package of the same name it replaces.
+    pub fn insert(&mut self, package: Package) -> Option<Package> {
+        self.packages.insert(package.name.clone(), package)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Package> {
+        self.packages.get(name)
+    }
+
+    /// The packages, sorted by name.
+    pub fn packages(&self) -> impl Iterator<Item = &Package> {
+        self.packages.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// The package `root` and everything it depends on, transitively, in an
+    /// order in which each package comes after all of its dependencies.
+    pub fn resolve(&self, root: &str) -> Result<Vec<&Package>, ResolveError> {
+        let package = self.get(root).ok_or_else(|| {
+            ResolveError::Missing(vec![Missing {
+                name: root.to_string(),
+                required_by: Vec::new(),
+            }])
+        })?;
+        self.walk([package])
+    }
+
+    /// Every package in the registry, each after all of its dependencies.
+    pub fn build_order(&self) -> Result<Vec<&Package>, ResolveError> {
+        self.walk(self.packages.values())
+    }
+
+    /// Depth-first search from each root in turn. A package is added to the
+    /// order once all of its dependencies have been; meeting a package that
+    /// is still being visited means the path on the stack is a cycle.
+    fn walk<'a>(
+        &'a self,
+        roots: impl IntoIterator<Item = &'a Package>,
+    ) -> Result<Vec<&'a Package>, ResolveError> {
+        let mut state: HashMap<&str, State> = HashMap::new();
+        let mut order = Vec::new();
+        let mut missing: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for root in roots {
+            if state.contains_key(root.name()) {
+                continue;
+            }
+            state.insert(root.name(), State::Visiting);
+            // each package on the path with the index of its next dependency
+            let mut stack = vec![(root, 0)];
+            while let Some(top) = stack.last_mut() {
+                let package = top.0;
+                let Some(dependency) = package.dependencies.get(top.1) else {
+                    state.insert(&package.name, State::Done);
+                    order.push(package);
+                    stack.pop();
+                    continue;
+                };
+                top.1 += 1;
+                let name = dependency.name();
+                match state.get(name) {
+                    Some(State::Done) => {}
+                    Some(State::Visiting) => {
+                        let start = stack
+                            .iter()
+                            .position(|(package, _)| package.name == name)
+                            .expect("a package being visited is on the stack");
+                        let mut path: Vec<String> =
+                            stack[start..].iter().map(|(p, _)| p.name.clone()).collect();
+                        path.push(name.to_string());
+                        return Err(ResolveError::Cycle(path));
+                    }
+                    None => match self.get(name) {
+                        Some(dependency) => {
+                            state.insert(name, State::Visiting);
+                            stack.push((dependency, 0));
+                        }
+                        None => {
+                            missing.entry(name).or_default().insert(&package.name);
+                        }
+                    },
+                }
+            }
+        }
+        if !missing.is_empty() {
+            return Err(ResolveError::Missing(
+                missing
+                    .into_iter()
+                    .map(|(name, required_by)| Missing {
+                        name: name.to_string(),
+                        required_by: required_by.into_iter().map(String::from).collect(),
+                    })
+                    .collect(),
+            ));
+        }
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::tests::{package, unvalidated};
+
+    fn registry_of(packages: &[(&str, &[&str])]) -> Registry {
+        let mut registry = Registry::new();
+        for (name, dependencies) in packages {
+            let dependencies: Vec<(&str, &str)> =
+                dependencies.iter().map(|d| (*d, "1.0")).collect();
+            registry.insert(package(name, "1.0.0", &dependencies));
+        }
+        registry
+    }
+
+    fn names(packages: Vec<&Package>) -> Vec<&str> {
+        packages.into_iter().map(Package::name).collect()
+    }
+
+    #[test]
+    fn dependencies_come_first() {
+        let registry = registry_of(&[
+            ("app", &["serde", "log"]),
+            ("serde", &["base64", "log"]),
+            ("log", &[]),
+            ("base64", &[]),
+            ("unrelated", &["log"]),
+        ]);
+        assert_eq!(
+            names(registry.resolve("app").unwrap()),
+            ["base64", "log", "serde", "app"]
+        );
+        assert_eq!(names(registry.resolve("log").unwrap()), ["log"]);
+        assert_eq!(
+            names(registry.build_order().unwrap()),
+            ["base64", "log", "serde", "app", "unrelated"]
+        );
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let registry = registry_of(&[("app", &["a"]), ("a", &["b"]), ("b", &["c"]), ("c", &["a"])]);
+        let err = registry.resolve("app").unwrap_err();
+        assert_eq!(
+            err,
+            ResolveError::Cycle(vec!["a".into(), "b".into(), "c".into(), "a".into()])
+        );
+        assert_eq!(err.to_string(), "dependency cycle: a -> b -> c -> a");
+
+        let mut narcissus = Registry::new();
+        narcissus.insert(unvalidated("narcissus", "1.0.0", &[("narcissus", "1.0")]));
+        assert_eq!(
+            narcissus.build_order().unwrap_err().to_string(),
+            "dependency cycle: narcissus -> narcissus"
+        );
+    }
+
+    #[test]
+    fn reports_all_missing_packages() {
+        let registry = registry_of(&[
+            ("app", &["serde", "log", "rand"]),
+            ("serde", &["base64", "rand"]),
+        ]);
+        let err = registry.resolve("app").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "missing packages: base64 (required by serde), log (required by app), \
+             rand (required by app, serde)"
+        );
+        assert_eq!(
+            registry.resolve("tokio").unwrap_err().to_string(),
+            "missing packages: tokio (requested)"
+        );
+    }
+}
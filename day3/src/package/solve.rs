@@ -0,0 +1,393 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter;
+
+use super::version::{Version, VersionError, VersionReq};
+use super::{Dependency, Package};
+
+/// Every published version of a set of packages.
+#[derive(Debug, Default)]
+pub struct Index {
+    packages: BTreeMap<String, BTreeMap<Version, Package>>,
+}
+
+/// A requirement one package places on another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Demand {
+    pub dependent: String,
+    pub dependent_version: Version,
+    pub requirement: VersionReq,
+}
+
+/// No version of `package` meets the demands made on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub package: String,
+    pub demands: Vec<Demand>,
+    /// Every version of the package in the index, oldest first.
+    pub available: Vec<Version>,
+}
+
+/// Why no set of versions could be chosen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The version expression of a dependency does not parse.
+    InvalidRequirement {
+        dependent: String,
+        dependency: String,
+        error: VersionError,
+    },
+    /// Every distinct conflict the search ran into, in the order found.
+    /// Each choice of versions that was tried failed on one of them.
+    Conflicts(Vec<Conflict>),
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "no version of {} satisfies every requirement:",
+            self.package
+        )?;
+        for demand in &self.demands {
+            writeln!(
+                f,
+                "  {} {} requires {} {}",
+                demand.dependent, demand.dependent_version, self.package, demand.requirement
+            )?;
+        }
+        write!(f, "available versions: ")?;
+        if self.available.is_empty() {
+            return write!(f, "none");
+        }
+        for (i, version) in self.available.iter().enumerate() {
+            write!(f, "{}{version}", if i == 0 { "" } else { ", " })?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::InvalidRequirement {
+                dependent,
+                dependency,
+                error,
+            } => write!(
+                f,
+                "{dependent} has an invalid requirement on {dependency}: {error}"
+            ),
+            SolveError::Conflicts(conflicts) => {
+                for (i, conflict) in conflicts.iter().enumerate() {
+                    write!(f, "{}{conflict}", if i == 0 { "" } else { "\n" })?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl Index {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a package, returning the package of the same name and version it
+    /// replaces.
+    pub fn insert(&mut self, package: Package) -> Option<Package> {
+        self.packages
+            .entry(package.name.clone())
+            .or_default()
+            .insert(package.version.clone(), package)
+    }
+
+    pub fn get(&self, name: &str, version: &Version) -> Option<&Package> {
+        self.packages.get(name)?.get(version)
+    }
+
+    /// The versions of a package, oldest first.
+    pub fn versions(&self, name: &str) -> impl Iterator<Item = &Version> {
+        self.packages.get(name).into_iter().flat_map(BTreeMap::keys)
+    }
+
+    /// Choose one version of every package `root` depends on, directly or
+    /// transitively, such that every dependency's version expression is met.
+    ///
+    /// Newer versions are tried first. When a choice leads to a conflict the
+    /// search backs up and tries the next older version.
+    pub fn solve<'a>(
+        &'a self,
+        root: &'a Package,
+    ) -> Result<BTreeMap<&'a str, &'a Package>, SolveError> {
+        let mut solver = Solver {
+            index: self,
+            root,
+            selected: BTreeMap::new(),
+            conflicts: Vec::new(),
+        };
+        if solver.search()? {
+            Ok(solver.selected)
+        } else {
+            Err(SolveError::Conflicts(solver.conflicts))
+        }
+    }
+}
+
+struct Solver<'a> {
+    index: &'a Index,
+    root: &'a Package,
+    selected: BTreeMap<&'a str, &'a Package>,
+    /// The conflicts found so far, to explain a failure. A conflict that
+    /// a later choice got past may still be among them.
+    conflicts: Vec<Conflict>,
+}
+
+fn requirement(package: &Package, dependency: &Dependency) -> Result<VersionReq, SolveError> {
+    dependency
+        .version_req()
+        .map_err(|error| SolveError::InvalidRequirement {
+            dependent: package.name.clone(),
+            dependency: dependency.name.clone(),
+            error,
+        })
+}
+
+impl<'a> Solver<'a> {
+    /// The root followed by the packages chosen so far.
+    fn dependents(&self) -> impl Iterator<Item = &'a Package> + '_ {
+        iter::once(self.root).chain(self.selected.values().copied())
+    }
+
+    /// A package that is depended on but has no version chosen yet.
+    fn next_unselected(&self) -> Option<&'a str> {
+        self.dependents()
+            .flat_map(|package| &package.dependencies)
+            .map(|dependency| dependency.name.as_str())
+            .find(|name| *name != self.root.name && !self.selected.contains_key(name))
+    }
+
+    /// The requirements placed on `name` so far.
+    fn demands(&self, name: &str) -> Result<Vec<(&'a Package, VersionReq)>, SolveError> {
+        let mut demands = Vec::new();
+        for package in self.dependents() {
+            for dependency in package.dependencies.iter().filter(|d| d.name == name) {
+                demands.push((package, requirement(package, dependency)?));
+            }
+        }
+        Ok(demands)
+    }
+
+    fn record(&mut self, package: &str, demands: Vec<(&Package, VersionReq)>) {
+        // the root is not looked up in the index; its only version is its own
+        let available = if package == self.root.name {
+            vec![self.root.version.clone()]
+        } else {
+            self.index.versions(package).cloned().collect()
+        };
+        let conflict = Conflict {
+            package: package.to_string(),
+            demands: demands
+                .into_iter()
+                .map(|(dependent, requirement)| Demand {
+                    dependent: dependent.name.clone(),
+                    dependent_version: dependent.version.clone(),
+                    requirement,
+                })
+                .collect(),
+            available,
+        };
+        if !self.conflicts.contains(&conflict) {
+            self.conflicts.push(conflict);
+        }
+    }
+
+    /// Whether the dependencies of `candidate` accept the versions already
+    /// chosen and the version of the root, recording a conflict if not.
+    fn fits(&mut self, candidate: &'a Package) -> Result<bool, SolveError> {
+        for dependency in &candidate.dependencies {
+            let chosen = if dependency.name == self.root.name {
+                self.root
+            } else {
+                match self.selected.get(dependency.name.as_str()) {
+                    Some(chosen) => *chosen,
+                    None => continue,
+                }
+            };
+            let requirement = requirement(candidate, dependency)?;
+            if !chosen.matches(&requirement) {
+                let mut demands = self.demands(&dependency.name)?;
+                demands.push((candidate, requirement));
+                self.record(&dependency.name, demands);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn search(&mut self) -> Result<bool, SolveError> {
+        let Some(name) = self.next_unselected() else {
+            return Ok(true);
+        };
+        let demands = self.demands(name)?;
+        let candidates: Vec<&'a Package> = self
+            .index
+            .packages
+            .get(name)
+            .into_iter()
+            .flat_map(|versions| versions.values().rev())
+            .filter(|package| demands.iter().all(|(_, req)| package.matches(req)))
+            .collect();
+        if candidates.is_empty() {
+            self.record(name, demands);
+            return Ok(false);
+        }
+        for candidate in candidates {
+            if !self.fits(candidate)? {
+                continue;
+            }
+            self.selected.insert(name, candidate);
+            if self.search()? {
+                return Ok(true);
+            }
+            self.selected.remove(name);
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::tests::{package, unvalidated};
+
+    fn index_of(packages: Vec<Package>) -> Index {
+        let mut index = Index::new();
+        for package in packages {
+            index.insert(package);
+        }
+        index
+    }
+
+    fn versions(solution: &BTreeMap<&str, &Package>) -> Vec<String> {
+        solution
+            .iter()
+            .map(|(name, package)| format!("{name} {}", package.version()))
+            .collect()
+    }
+
+    #[test]
+    fn picks_newest_matching_versions() {
+        let index = index_of(vec![
+            package("log", "0.3.9", &[]),
+            package("log", "0.4.0", &[]),
+            package("log", "0.4.20", &[]),
+            package("log", "0.5.0", &[]),
+            package("serde", "1.0.0", &[("log", "^0.4")]),
+            package("serde", "1.1.0", &[("log", ">=0.4, <0.4.10")]),
+        ]);
+        let app = package("app", "0.1.0", &[("serde", "1"), ("log", "*")]);
+        let solution = index.solve(&app).unwrap();
+        assert_eq!(versions(&solution), ["log 0.4.0", "serde 1.1.0"]);
+    }
+
+    #[test]
+    fn backtracks_on_conflicts() {
+        // the newest `a` needs a `c` that `b` cannot work with
+        let index = index_of(vec![
+            package("a", "1.0.0", &[("c", "^1")]),
+            package("a", "1.1.0", &[("c", "^2")]),
+            package("b", "1.0.0", &[("c", "^1")]),
+            package("c", "1.0.0", &[]),
+            package("c", "2.0.0", &[]),
+        ]);
+        let app = package("app", "0.1.0", &[("a", "^1"), ("b", "^1")]);
+        let solution = index.solve(&app).unwrap();
+        assert_eq!(versions(&solution), ["a 1.0.0", "b 1.0.0", "c 1.0.0"]);
+    }
+
+    #[test]
+    fn explains_failures() {
+        let index = index_of(vec![
+            package("a", "1.0.0", &[("c", "^2")]),
+            package("b", "1.0.0", &[("c", "=1.0.0")]),
+            package("c", "1.0.0", &[]),
+            package("c", "2.0.0", &[]),
+        ]);
+        let app = package("app", "0.1.0", &[("a", "^1"), ("b", "^1")]);
+        let err = index.solve(&app).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no version of c satisfies every requirement:\n  \
+             a 1.0.0 requires c ^2\n  \
+             b 1.0.0 requires c =1.0.0\n\
+             available versions: 1.0.0, 2.0.0"
+        );
+
+        let app = package("app", "0.1.0", &[("missing", "^1")]);
+        assert_eq!(
+            index.solve(&app).unwrap_err().to_string(),
+            "no version of missing satisfies every requirement:\n  \
+             app 0.1.0 requires missing ^1\n\
+             available versions: none"
+        );
+
+        let app = unvalidated("app", "0.1.0", &[("a", "one")]);
+        assert!(matches!(
+            index.solve(&app),
+            Err(SolveError::InvalidRequirement { .. })
+        ));
+    }
+
+    #[test]
+    fn checks_requirements_on_the_root() {
+        // a plugin that depends back on the application it extends
+        let mut index = index_of(vec![
+            package("plugin", "1.0.0", &[("app", "^1")]),
+            package("plugin", "1.1.0", &[("app", "^2")]),
+        ]);
+        let app = package("app", "1.4.0", &[("plugin", "^1")]);
+        let solution = index.solve(&app).unwrap();
+        assert_eq!(versions(&solution), ["plugin 1.0.0"]);
+
+        index = index_of(vec![package("plugin", "1.1.0", &[("app", "^2")])]);
+        assert_eq!(
+            index.solve(&app).unwrap_err().to_string(),
+            "no version of app satisfies every requirement:\n  \
+             plugin 1.1.0 requires app ^2\n\
+             available versions: 1.4.0"
+        );
+    }
+
+    #[test]
+    fn explains_every_branch() {
+        // the newest `a` conflicts with `b` over `c`, and the older one
+        // needs a package that does not exist
+        let index = index_of(vec![
+            package("a", "1.0.0", &[("d", "^1")]),
+            package("a", "1.1.0", &[("c", "^2")]),
+            package("b", "1.0.0", &[("c", "^1")]),
+            package("c", "1.0.0", &[]),
+            package("c", "2.0.0", &[]),
+        ]);
+        let app = package("app", "0.1.0", &[("b", "^1"), ("a", "^1")]);
+        let err = index.solve(&app).unwrap_err();
+        let SolveError::Conflicts(conflicts) = &err else {
+            panic!("expected conflicts, got {err:?}");
+        };
+        let packages: Vec<&str> = conflicts.iter().map(|c| c.package.as_str()).collect();
+        assert_eq!(packages, ["c", "d"]);
+        assert_eq!(
+            err.to_string(),
+            "no version of c satisfies every requirement:\n  \
+             a 1.1.0 requires c ^2\n  \
+             b 1.0.0 requires c ^1\n\
+             available versions: 1.0.0, 2.0.0\n\
+             no version of d satisfies every requirement:\n  \
+             a 1.0.0 requires d ^1\n\
+             available versions: none"
+        );
+    }
+}
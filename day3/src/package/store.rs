@@ -0,0 +1,536 @@
+//! A registry kept in a directory, for use without a network:
+//!
+//! ```text
+//! <root>/index
+//! <root>/packages/<name>/<version>.toml
+//! ```
+//!
+//! Each package version is stored as a manifest of its own. The index has
+//! one line per package version with its name, version, language and
+//! authors as quoted strings, so searches do not have to read every manifest:
+//!
+//! ```text
+//! "serde" "4.0.0" "rust" "djmitche"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::manifest::{
+    Cursor, ManifestError, SyntaxError, language_from_name, language_name, quoted,
+};
+use super::solve::Index;
+use super::version::Version;
+use super::{Language, Package};
+
+const INDEX: &str = "index";
+const PACKAGES: &str = "packages";
+
+/// What the index knows about a package version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    pub name: String,
+    pub version: Version,
+    pub language: Option<Language>,
+    pub authors: Vec<String>,
+}
+
+/// Why the registry could not be read or written.
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    /// The index file is malformed.
+    Index(SyntaxError),
+    /// A stored manifest could not be read.
+    Manifest {
+        path: PathBuf,
+        error: ManifestError,
+    },
+    /// The name cannot be used as a directory name. Names may only contain
+    /// ASCII letters, digits, `-` and `_`.
+    InvalidName(String),
+    /// This version of the package is already published; published
+    /// versions never change.
+    AlreadyPublished {
+        name: String,
+        version: Version,
+    },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(err) => write!(f, "registry i/o failed: {err}"),
+            StoreError::Index(err) => write!(f, "registry index is corrupt: {err}"),
+            StoreError::Manifest { path, error } => write!(f, "{}: {error}", path.display()),
+            StoreError::InvalidName(name) => write!(f, "invalid package name {name:?}"),
+            StoreError::AlreadyPublished { name, version } => {
+                write!(f, "{name} {version} is already published")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Io(err) => Some(err),
+            StoreError::Index(err) => Some(err),
+            StoreError::Manifest { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(err: io::Error) -> Self {
+        StoreError::Io(err)
+    }
+}
+
+/// What to look for with [`DiskRegistry::search`]. Every criterion that is
+/// set has to match.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    name_prefix: Option<String>,
+    author: Option<String>,
+    language: Option<Language>,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Packages whose name starts with `prefix`.
+    pub fn name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.name_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Packages with an author that contains `author`, ignoring case.
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into().to_lowercase());
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    fn matches(&self, listing: &Listing) -> bool {
+        self.name_prefix
+            .as_ref()
+            .is_none_or(|prefix| listing.name.starts_with(prefix.as_str()))
+            && self.author.as_ref().is_none_or(|author| {
+                listing
+                    .authors
+                    .iter()
+                    .any(|a| a.to_lowercase().contains(author.as_str()))
+            })
+            && self
+                .language
+                .is_none_or(|language| listing.language == Some(language))
+    }
+}
+
+/// A registry of package versions stored in a directory.
+#[derive(Debug)]
+pub struct DiskRegistry {
+    root: PathBuf,
+    /// The index, sorted by name and version.
+    listings: BTreeMap<(String, Version), Listing>,
+}
+
+fn check_name(name: &str) -> Result<(), StoreError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(StoreError::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+fn parse_index(input: &str) -> Result<Vec<Listing>, SyntaxError> {
+    let mut listings = Vec::new();
+    for (i, text) in input.lines().enumerate() {
+        let mut cursor = Cursor::new(i + 1, text);
+        if cursor.at_end() {
+            continue;
+        }
+        let mut fields = Vec::new();
+        while !cursor.at_end() {
+            fields.push((cursor.pos, cursor.string()?));
+        }
+        let [
+            (_, name),
+            (version_start, version),
+            (language_start, language),
+            authors @ ..,
+        ] = fields.as_slice()
+        else {
+            return Err(cursor.error_at(0, "expected a name, a version and a language"));
+        };
+        listings.push(Listing {
+            name: name.clone(),
+            version: version
+                .parse()
+                .map_err(|_| cursor.error_at(*version_start, "invalid version"))?,
+            language: match language.as_str() {
+                "" => None,
+                language => Some(
+                    language_from_name(language)
+                        .ok_or_else(|| cursor.error_at(*language_start, "unknown language"))?,
+                ),
+            },
+            authors: authors.iter().map(|(_, author)| author.clone()).collect(),
+        });
+    }
+    Ok(listings)
+}
+
+impl DiskRegistry {
+    /// Start an empty registry in `root`, which is created if it does not
+    /// exist. Fails if there already is a registry there.
+    pub fn create(root: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let registry = Self {
+            root: root.into(),
+            listings: BTreeMap::new(),
+        };
+        if registry.root.join(INDEX).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already holds a registry", registry.root.display()),
+            )
+            .into());
+        }
+        fs::create_dir_all(registry.root.join(PACKAGES))?;
+        registry.write_index()?;
+        Ok(registry)
+    }
+
+    /// Open a registry that [`create`](DiskRegistry::create) made earlier.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let root = root.into();
+        let index = fs::read_to_string(root.join(INDEX))?;
+        let listings = parse_index(&index)
+            .map_err(StoreError::Index)?
+            .into_iter()
+            .map(|listing| ((listing.name.clone(), listing.version.clone()), listing))
+            .collect();
+        Ok(Self { root, listings })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn manifest_path(&self, name: &str, version: &Version) -> PathBuf {
+        self.root
+            .join(PACKAGES)
+            .join(name)
+            .join(format!("{version}.toml"))
+    }
+
+    /// Replace the index file as a whole, so that a crash cannot leave half
+    /// of it behind.
+    fn write_index(&self) -> io::Result<()> {
+        let mut index = String::new();
+        for listing in self.listings.values() {
+            let language = listing.language.map_or("", language_name);
+            let mut fields = vec![
+                quoted(&listing.name),
+                quoted(&listing.version.to_string()),
+                quoted(language),
+            ];
+            fields.extend(listing.authors.iter().map(|author| quoted(author)));
+            index.push_str(&fields.join(" "));
+            index.push('\n');
+        }
+        let partial = self.root.join(format!("{INDEX}.tmp"));
+        fs::write(&partial, index)?;
+        fs::rename(partial, self.root.join(INDEX))
+    }
+
+    /// Store a new version of a package.
+    pub fn publish(&mut self, package: &Package) -> Result<(), StoreError> {
+        check_name(&package.name)?;
+        let key = (package.name.clone(), package.version.clone());
+        if self.listings.contains_key(&key) {
+            let (name, version) = key;
+            return Err(StoreError::AlreadyPublished { name, version });
+        }
+        // a manifest is only seen once the index lists it, so writing the
+        // index is what publishes the version
+        let path = self.manifest_path(&package.name, &key.1);
+        fs::create_dir_all(path.parent().expect("a manifest is inside the registry"))?;
+        fs::write(&path, package.to_manifest())?;
+        let listing = Listing {
+            name: package.name.clone(),
+            version: key.1.clone(),
+            language: package.language,
+            authors: package.authors.clone(),
+        };
+        self.listings.insert(key.clone(), listing);
+        if let Err(err) = self.write_index() {
+            self.listings.remove(&key);
+            let _ = fs::remove_file(&path);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    /// Read a stored package version, or `None` if it was never published.
+    pub fn load(&self, name: &str, version: &Version) -> Result<Option<Package>, StoreError> {
+        // the index may have been edited by hand
+        check_name(name)?;
+        if !self
+            .listings
+            .contains_key(&(name.to_string(), version.clone()))
+        {
+            return Ok(None);
+        }
+        let path = self.manifest_path(name, version);
+        let manifest = fs::read_to_string(&path)?;
+        Package::from_manifest(&manifest)
+            .map(Some)
+            .map_err(|error| StoreError::Manifest { path, error })
+    }
+
+    /// The published versions of a package, oldest first.
+    pub fn versions(&self, name: &str) -> Vec<&Version> {
+        self.listings
+            .range((name.to_string(), Version::lowest())..)
+            .take_while(|((listed, _), _)| listed == name)
+            .map(|((_, version), _)| version)
+            .collect()
+    }
+
+    /// Read every stored package version into an [`Index`], so that
+    /// dependencies can be solved against the registry.
+    pub fn to_index(&self) -> Result<Index, StoreError> {
+        let mut index = Index::new();
+        for (name, version) in self.listings.keys() {
+            if let Some(package) = self.load(name, version)? {
+                index.insert(package);
+            }
+        }
+        Ok(index)
+    }
+
+    /// Every package version that matches `query`, sorted by name and
+    /// version.
+    pub fn search(&self, query: &Query) -> Vec<&Listing> {
+        self.listings
+            .values()
+            .filter(|listing| query.matches(listing))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::{Dependency, PackageBuilder};
+
+    /// A fresh directory for one test, removed again when the test ends,
+    /// whether it passed or not.
+    struct ScratchDir(PathBuf);
+
+    impl std::ops::Deref for ScratchDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl From<&ScratchDir> for PathBuf {
+        fn from(dir: &ScratchDir) -> Self {
+            dir.0.clone()
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn scratch_dir(test: &str) -> ScratchDir {
+        let dir = std::env::temp_dir().join(format!("day3-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ScratchDir(dir)
+    }
+
+    fn package(name: &str, version: &str, authors: &[&str], language: Language) -> Package {
+        PackageBuilder::new(name)
+            .version(version)
+            .authors(authors.iter().map(|a| a.to_string()).collect())
+            .language(language)
+            .dependency(Dependency::new("base64", "^0.13"))
+            .build()
+            .unwrap()
+    }
+
+    fn names(listings: Vec<&Listing>) -> Vec<String> {
+        listings
+            .into_iter()
+            .map(|listing| format!("{} {}", listing.name, listing.version))
+            .collect()
+    }
+
+    #[test]
+    fn publish_load_and_reopen() {
+        let dir = scratch_dir("publish");
+        let mut registry = DiskRegistry::create(&dir).unwrap();
+        let serde = package("serde", "1.0.0", &["djmitche", "Tab\there"], Language::Rust);
+        registry.publish(&serde).unwrap();
+        registry
+            .publish(&package("serde", "1.1.0", &[], Language::Rust))
+            .unwrap();
+        assert!(matches!(
+            registry.publish(&serde),
+            Err(StoreError::AlreadyPublished { .. })
+        ));
+        assert!(matches!(
+            registry.publish(&package("../evil", "1.0.0", &[], Language::Rust)),
+            Err(StoreError::InvalidName(_))
+        ));
+        assert!(dir.join("packages/serde/1.0.0.toml").is_file());
+        assert!(matches!(
+            DiskRegistry::create(&dir),
+            Err(StoreError::Io(err)) if err.kind() == io::ErrorKind::AlreadyExists
+        ));
+
+        let reopened = DiskRegistry::open(&dir).unwrap();
+        let version = "1.0.0".parse().unwrap();
+        assert_eq!(reopened.load("serde", &version).unwrap(), Some(serde));
+        assert_eq!(reopened.load("log", &version).unwrap(), None);
+        assert_eq!(
+            reopened.versions("serde"),
+            [&"1.0.0".parse().unwrap(), &"1.1.0".parse().unwrap()]
+        );
+        assert!(reopened.versions("log").is_empty());
+        assert_eq!(
+            reopened.search(&Query::new()),
+            registry.search(&Query::new())
+        );
+    }
+
+    #[test]
+    fn search() {
+        let dir = scratch_dir("search");
+        let mut registry = DiskRegistry::create(&dir).unwrap();
+        for package in [
+            package("serde", "1.0.0", &["David Tolnay"], Language::Rust),
+            package("serde_json", "1.0.0", &["dtolnay", "Erick"], Language::Rust),
+            package("servlet", "4.0.0", &["Oracle"], Language::Java),
+            package("moose", "2.2.0", &["Stevan"], Language::Perl),
+        ] {
+            registry.publish(&package).unwrap();
+        }
+        let registry = DiskRegistry::open(&dir).unwrap();
+        assert_eq!(
+            names(registry.search(&Query::new().name_prefix("ser"))),
+            ["serde 1.0.0", "serde_json 1.0.0", "servlet 4.0.0"]
+        );
+        assert_eq!(
+            names(registry.search(&Query::new().author("TOLNAY"))),
+            ["serde 1.0.0", "serde_json 1.0.0"]
+        );
+        assert_eq!(
+            names(registry.search(&Query::new().name_prefix("ser").language(Language::Java))),
+            ["servlet 4.0.0"]
+        );
+        assert!(registry.search(&Query::new().author("nobody")).is_empty());
+    }
+
+    #[test]
+    fn solve_against_the_registry() {
+        let dir = scratch_dir("solve");
+        let mut registry = DiskRegistry::create(&dir).unwrap();
+        for (name, version) in [("serde", "1.0.0"), ("serde", "1.1.0"), ("serde", "2.0.0")] {
+            registry
+                .publish(&package(name, version, &[], Language::Rust))
+                .unwrap();
+        }
+        for version in ["0.13.0", "0.13.1", "0.21.0"] {
+            registry
+                .publish(
+                    &PackageBuilder::new("base64")
+                        .version(version)
+                        .build()
+                        .unwrap(),
+                )
+                .unwrap();
+        }
+
+        let index = DiskRegistry::open(&dir).unwrap().to_index().unwrap();
+        let app = PackageBuilder::new("app")
+            .version("0.1.0")
+            .dependency(Dependency::new("serde", "^1"))
+            .build()
+            .unwrap();
+        let solution: Vec<String> = index
+            .solve(&app)
+            .unwrap()
+            .into_iter()
+            .map(|(name, package)| format!("{name} {}", package.version()))
+            .collect();
+        assert_eq!(solution, ["base64 0.13.1", "serde 1.1.0"]);
+    }
+
+    #[test]
+    fn failed_publish_leaves_nothing_behind() {
+        let dir = scratch_dir("failed");
+        let mut registry = DiskRegistry::create(&dir).unwrap();
+        let serde = package("serde", "1.0.0", &[], Language::Rust);
+        // the index cannot be replaced while a directory is in the way
+        fs::create_dir(dir.join(format!("{INDEX}.tmp"))).unwrap();
+        assert!(matches!(registry.publish(&serde), Err(StoreError::Io(_))));
+        assert!(registry.versions("serde").is_empty());
+        assert!(!dir.join("packages/serde/1.0.0.toml").exists());
+        assert!(
+            DiskRegistry::open(&dir)
+                .unwrap()
+                .versions("serde")
+                .is_empty()
+        );
+
+        fs::remove_dir(dir.join(format!("{INDEX}.tmp"))).unwrap();
+        registry.publish(&serde).unwrap();
+        for (name, version) in [("serde-derive", "1.0.0"), ("serde", "0.0.0-alpha")] {
+            registry
+                .publish(&package(name, version, &[], Language::Rust))
+                .unwrap();
+        }
+        assert_eq!(
+            registry.versions("serde"),
+            [&"0.0.0-alpha".parse().unwrap(), &"1.0.0".parse().unwrap()]
+        );
+    }
+
+    #[test]
+    fn corrupt_index() {
+        let dir = scratch_dir("corrupt");
+        DiskRegistry::create(&dir).unwrap();
+        fs::write(dir.join(INDEX), "\"serde\" \"1.0\" \"rust\"\n").unwrap();
+        let err = DiskRegistry::open(&dir).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "registry index is corrupt: 1:9: invalid version"
+        );
+        fs::write(dir.join(INDEX), "\"serde\"\n").unwrap();
+        assert!(matches!(
+            DiskRegistry::open(&dir),
+            Err(StoreError::Index(_))
+        ));
+    }
+}
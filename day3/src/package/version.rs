@@ -0,0 +1,569 @@
+//! Semantic versions and the requirements dependencies place on them.
+//!
+//! Versions follow [Semantic Versioning](https://semver.org). Requirements
+//! use the syntax of Cargo: `^1.2`, `~1.2.3`, `1.*`, `>=1.2, <1.5` and so on,
+//! where a bare version such as `1.2` means `^1.2`.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+use std::str::FromStr;
+
+/// A dot-separated part of a pre-release.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Identifier {
+    /// Numeric identifiers sort numerically and before alphanumeric ones,
+    /// which the order of the variants takes care of.
+    Numeric(u64),
+    AlphaNumeric(String),
+}
+
+/// A version such as `1.2.3`, `1.0.0-alpha.1` or `1.0.0+build.5`.
+///
+/// Versions are ordered by precedence: a pre-release comes before the release
+/// of the same version. Two versions that differ only in build metadata have
+/// the same precedence; they are ordered by the metadata so that the order is
+/// total and agrees with `==`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Empty for a release.
+    pub pre: Vec<Identifier>,
+    /// Empty if there is no build metadata.
+    pub build: String,
+}
+
+/// Why a version or a requirement could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionError {
+    /// There is nothing to parse.
+    Empty,
+    /// A version has fewer than three numbers, such as `0.4`. `part` names
+    /// the first one that is missing: `"minor"` or `"patch"`.
+    MissingPart { version: String, part: &'static str },
+    /// A version number is empty, is not a number or has a leading zero.
+    InvalidNumber(String),
+    /// A pre-release or build identifier is empty or has invalid characters.
+    InvalidIdentifier(String),
+    /// Input that does not belong in a version or requirement.
+    Unexpected(String),
+}
+
+impl fmt::Display for VersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionError::Empty => write!(f, "empty version"),
+            VersionError::MissingPart { version, part } => {
+                write!(f, "version {version} has no {part} number")
+            }
+            VersionError::InvalidNumber(part) => write!(f, "invalid version number {part:?}"),
+            VersionError::InvalidIdentifier(part) => write!(f, "invalid identifier {part:?}"),
+            VersionError::Unexpected(part) => write!(f, "unexpected {part:?}"),
+        }
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+fn parse_number(part: &str) -> Result<u64, VersionError> {
+    let invalid = || VersionError::InvalidNumber(part.to_string());
+    if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    if part.len() > 1 && part.starts_with('0') {
+        return Err(invalid());
+    }
+    part.parse().map_err(|_| invalid())
+}
+
+fn check_identifier(part: &str) -> Result<(), VersionError> {
+    if part.is_empty() || !part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return Err(VersionError::InvalidIdentifier(part.to_string()));
+    }
+    Ok(())
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+            build: String::new(),
+        }
+    }
+
+    /// The version that comes before every other, `0.0.0-0`.
+    pub fn lowest() -> Self {
+        Self {
+            pre: vec![Identifier::Numeric(0)],
+            ..Self::new(0, 0, 0)
+        }
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    fn triple(&self) -> (u64, u64, u64) {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = VersionError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.is_empty() {
+            return Err(VersionError::Empty);
+        }
+        let (rest, build) = input.split_once('+').unwrap_or((input, ""));
+        if input.contains('+') {
+            build.split('.').try_for_each(check_identifier)?;
+        }
+        let (numbers, pre) = rest.split_once('-').unwrap_or((rest, ""));
+        let mut pre_identifiers = Vec::new();
+        if rest.contains('-') {
+            for part in pre.split('.') {
+                check_identifier(part)?;
+                pre_identifiers.push(if part.bytes().all(|b| b.is_ascii_digit()) {
+                    Identifier::Numeric(
+                        parse_number(part)
+                            .map_err(|_| VersionError::InvalidIdentifier(part.to_string()))?,
+                    )
+                } else {
+                    Identifier::AlphaNumeric(part.to_string())
+                });
+            }
+        }
+        let mut parts = numbers.split('.');
+        let mut number = |name| match parts.next() {
+            Some(part) => parse_number(part),
+            None => Err(VersionError::MissingPart {
+                version: input.to_string(),
+                part: name,
+            }),
+        };
+        let (major, minor, patch) = (number("major")?, number("minor")?, number("patch")?);
+        if let Some(extra) = parts.next() {
+            return Err(VersionError::Unexpected(format!(".{extra}")));
+        }
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre: pre_identifiers,
+            build: build.to_string(),
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.triple()
+            .cmp(&other.triple())
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identifier::Numeric(n) => write!(f, "{n}"),
+            Identifier::AlphaNumeric(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+fn write_pre(f: &mut fmt::Formatter<'_>, pre: &[Identifier]) -> fmt::Result {
+    for (i, identifier) in pre.iter().enumerate() {
+        write!(f, "{}{identifier}", if i == 0 { "-" } else { "." })?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        write_pre(f, &self.pre)?;
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+    /// `1.*` or `1.2.*`
+    Wildcard,
+}
+
+/// One comparison of a requirement, such as `>=1.2` or `~1.2.3`. Minor and
+/// patch may be left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<Identifier>,
+}
+
+/// A requirement on a version, such as `^1.2` or `>=1.2, <1.5`.
+///
+/// A version matches if it satisfies every comma-separated comparator. A
+/// pre-release only matches if one of the comparators names a pre-release of
+/// the same major, minor and patch version, so that `^1.0` does not pick up
+/// `1.1.0-alpha` by accident.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    /// Empty for `*`, which matches every release.
+    comparators: Vec<Comparator>,
+}
+
+impl Comparator {
+    fn parse(input: &str) -> Result<Self, VersionError> {
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| Some((op, input.strip_prefix(prefix)?)))
+        .unwrap_or((Op::Caret, input));
+        let rest = rest.trim_start();
+        if rest.is_empty() {
+            return Err(VersionError::Empty);
+        }
+        if rest.contains(['-', '+']) {
+            let version: Version = rest.parse()?;
+            return Ok(Self {
+                op,
+                major: version.major,
+                minor: Some(version.minor),
+                patch: Some(version.patch),
+                pre: version.pre,
+            });
+        }
+
+        let bare = input.len() == rest.len();
+        let mut numbers = [None; 3];
+        let mut wildcard = false;
+        let mut parts = rest.split('.');
+        for (i, part) in parts.by_ref().take(3).enumerate() {
+            if matches!(part, "*" | "x" | "X") {
+                // only a trailing part of a version without operator may be a
+                // wildcard, and not the major version
+                if !bare || i == 0 {
+                    return Err(VersionError::Unexpected(part.to_string()));
+                }
+                wildcard = true;
+            } else if wildcard {
+                return Err(VersionError::Unexpected(part.to_string()));
+            } else {
+                numbers[i] = Some(parse_number(part)?);
+            }
+        }
+        if let Some(extra) = parts.next() {
+            return Err(VersionError::Unexpected(format!(".{extra}")));
+        }
+        Ok(Self {
+            op: if wildcard { Op::Wildcard } else { op },
+            major: numbers[0].expect("the major version is always parsed"),
+            minor: numbers[1],
+            patch: numbers[2],
+            pre: Vec::new(),
+        })
+    }
+
+    /// The versions this comparator allows, ignoring the rule for
+    /// pre-releases.
+    fn range(&self) -> (Bound<Version>, Bound<Version>) {
+        use Bound::{Excluded, Included, Unbounded};
+
+        let major = self.major;
+        let version = |minor: u64, patch: u64| Version::new(major, minor, patch);
+        // the first version past a given major or minor version
+        let next_major = || major.checked_add(1).map(|m| Version::new(m, 0, 0));
+        let next_minor = |minor: u64| minor.checked_add(1).map(|m| version(m, 0));
+        let below = |end: Option<Version>| end.map_or(Unbounded, Excluded);
+        let exact = |full: Version| match (self.minor, self.patch) {
+            (Some(_), Some(_)) => (Included(full.clone()), Included(full)),
+            (Some(minor), None) => (Included(version(minor, 0)), below(next_minor(minor))),
+            (None, _) => (Included(version(0, 0)), below(next_major())),
+        };
+
+        let full = Version {
+            pre: self.pre.clone(),
+            ..version(self.minor.unwrap_or(0), self.patch.unwrap_or(0))
+        };
+        match (self.op, self.minor, self.patch) {
+            (Op::Exact | Op::Wildcard, _, _) => exact(full),
+            (Op::Greater, Some(_), Some(_)) => (Excluded(full), Unbounded),
+            (Op::Greater, Some(minor), None) => (
+                next_minor(minor).map_or(Excluded(full), Included),
+                Unbounded,
+            ),
+            (Op::Greater, None, _) => (next_major().map_or(Excluded(full), Included), Unbounded),
+            (Op::GreaterEq, _, _) => (Included(full), Unbounded),
+            (Op::Less, _, _) => (Unbounded, Excluded(full)),
+            (Op::LessEq, Some(_), Some(_)) => (Unbounded, Included(full)),
+            (Op::LessEq, Some(minor), None) => (Unbounded, below(next_minor(minor))),
+            (Op::LessEq, None, _) => (Unbounded, below(next_major())),
+            (Op::Tilde, Some(minor), Some(_)) => (Included(full), below(next_minor(minor))),
+            (Op::Tilde, _, _) => exact(full),
+            (Op::Caret, Some(minor), patch) => {
+                let end = match (major, minor, patch) {
+                    (0, 0, Some(patch)) => patch.checked_add(1).map(|p| version(0, p)),
+                    (0, _, _) => next_minor(minor),
+                    _ => next_major(),
+                };
+                (Included(full), below(end))
+            }
+            (Op::Caret, None, _) => exact(full),
+        }
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        // build metadata plays no part in matching
+        if !version.build.is_empty() {
+            return self.matches(&Version {
+                build: String::new(),
+                ..version.clone()
+            });
+        }
+        self.range().contains(version)
+    }
+}
+
+impl VersionReq {
+    /// The requirement `*`, which every release matches.
+    pub const STAR: VersionReq = VersionReq {
+        comparators: Vec::new(),
+    };
+
+    pub fn matches(&self, version: &Version) -> bool {
+        if !self.comparators.iter().all(|c| c.matches(version)) {
+            return false;
+        }
+        !version.is_prerelease()
+            || self.comparators.iter().any(|c| {
+                !c.pre.is_empty()
+                    && (Some(c.major), c.minor, c.patch)
+                        == (
+                            Some(version.major),
+                            Some(version.minor),
+                            Some(version.patch),
+                        )
+            })
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = VersionError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if matches!(input, "*" | "x" | "X") {
+            return Ok(Self::STAR);
+        }
+        let comparators = input
+            .split(',')
+            .map(|part| Comparator::parse(part.trim()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { comparators })
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Tilde => "~",
+            Op::Caret => "^",
+            Op::Wildcard => "",
+        };
+        write!(f, "{op}{}", self.major)?;
+        for part in [self.minor, self.patch] {
+            match part {
+                Some(n) => write!(f, ".{n}")?,
+                None if self.op == Op::Wildcard => return write!(f, ".*"),
+                None => break,
+            }
+        }
+        write_pre(f, &self.pre)
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.comparators.is_empty() {
+            return write!(f, "*");
+        }
+        for (i, comparator) in self.comparators.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{comparator}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(input: &str) -> Version {
+        input.parse().unwrap()
+    }
+
+    fn req(input: &str) -> VersionReq {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display_versions() {
+        let version = v("1.2.3-alpha.10+build.5");
+        assert_eq!(version.triple(), (1, 2, 3));
+        assert_eq!(
+            version.pre,
+            [
+                Identifier::AlphaNumeric("alpha".into()),
+                Identifier::Numeric(10)
+            ]
+        );
+        assert_eq!(version.build, "build.5");
+        for input in [
+            "0.0.0",
+            "1.2.3-alpha.10+build.5",
+            "1.0.0+001",
+            "1.0.0-x-y.7",
+        ] {
+            assert_eq!(v(input).to_string(), input);
+        }
+
+        for (input, err) in [
+            ("", VersionError::Empty),
+            (
+                "1.2",
+                VersionError::MissingPart {
+                    version: "1.2".into(),
+                    part: "patch",
+                },
+            ),
+            (
+                "1",
+                VersionError::MissingPart {
+                    version: "1".into(),
+                    part: "minor",
+                },
+            ),
+            ("1.2.", VersionError::InvalidNumber("".into())),
+            ("1.02.3", VersionError::InvalidNumber("02".into())),
+            ("1.2.x", VersionError::InvalidNumber("x".into())),
+            ("1.2.3.4", VersionError::Unexpected(".4".into())),
+            ("1.2.3-", VersionError::InvalidIdentifier("".into())),
+            ("1.2.3-01", VersionError::InvalidIdentifier("01".into())),
+            ("1.2.3+a_b", VersionError::InvalidIdentifier("a_b".into())),
+        ] {
+            assert_eq!(input.parse::<Version>(), Err(err), "{input}");
+        }
+    }
+
+    #[test]
+    fn versions_are_ordered_by_precedence() {
+        // the example from the specification
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.0+a",
+            "1.0.1",
+            "1.10.0",
+            "2.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn requirements() {
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            ("1.2.3", &["1.2.3", "1.9.0"], &["1.2.2", "2.0.0"]),
+            ("^0.2.3", &["0.2.3", "0.2.9"], &["0.3.0", "0.2.2"]),
+            ("^0.0.3", &["0.0.3"], &["0.0.4", "0.0.2"]),
+            ("^0.0", &["0.0.0", "0.0.7"], &["0.1.0"]),
+            ("^1", &["1.0.0", "1.9.9"], &["2.0.0", "0.9.0"]),
+            ("~1.2.3", &["1.2.3", "1.2.9"], &["1.3.0", "1.2.2"]),
+            ("~1", &["1.0.0", "1.9.0"], &["2.0.0"]),
+            ("=1.2.3", &["1.2.3", "1.2.3+build"], &["1.2.4"]),
+            ("=1.2", &["1.2.0", "1.2.9"], &["1.3.0"]),
+            ("1.*", &["1.0.0", "1.9.0"], &["2.0.0", "0.1.0"]),
+            ("1.2.x", &["1.2.0", "1.2.9"], &["1.3.0"]),
+            ("*", &["0.0.1", "99.0.0"], &["1.0.0-alpha"]),
+            ("> 1.2", &["1.3.0"], &["1.2.9"]),
+            ("<= 1.2", &["1.2.9", "0.1.0"], &["1.3.0"]),
+            (">=1.2, <1.5", &["1.2.0", "1.4.9"], &["1.5.0", "1.1.0"]),
+            (
+                ">=1.0.0-alpha, <2",
+                &["1.0.0-beta", "1.3.0"],
+                &["1.1.0-beta", "0.9.0"],
+            ),
+        ];
+        for (requirement, matching, other) in cases {
+            let requirement = req(requirement);
+            for version in *matching {
+                assert!(requirement.matches(&v(version)), "{requirement} {version}");
+            }
+            for version in *other {
+                assert!(!requirement.matches(&v(version)), "{requirement} {version}");
+            }
+        }
+
+        assert_eq!(req(" >=1.2 ,<1.5").to_string(), ">=1.2, <1.5");
+        assert_eq!(req("1.2.*").to_string(), "1.2.*");
+        assert_eq!(req("x").to_string(), "*");
+
+        for input in ["", ">=", "1.2.3.4", "^1.*", "*.1", "1.*.2", "1.2, ", "!1"] {
+            assert!(input.parse::<VersionReq>().is_err(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#[cfg(test)]
+mod tree_model;
+
+/// Run `f` on a thread with a 64 KiB stack, small enough that recursing
+/// once per element of a long list or tree overflows it.
+#[cfg(test)]
+pub(crate) fn on_small_stack(f: impl FnOnce() + Send + 'static) {
+    std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
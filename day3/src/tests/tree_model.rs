@@ -0,0 +1,168 @@
+//! Model-based tests: random sequences of operations are applied both to a
+//! `BinaryTree` and to `std::collections::BTreeSet`, and the two must agree
+//! after every step. A failing sequence is shrunk before it is reported.
+
+use std::collections::BTreeSet;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::tree::BinaryTree;
+
+const CASES: u64 = 300;
+const MAX_OPS: usize = 120;
+// a small domain makes duplicate inserts and hits on remove likely
+const MAX_VALUE: i32 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Insert(i32),
+    Remove(i32),
+    PopMin,
+    PopMax,
+    Clear,
+}
+
+fn random_ops(rng: &mut StdRng) -> Vec<Op> {
+    let len = rng.random_range(0..=MAX_OPS);
+    (0..len)
+        .map(|_| match rng.random_range(0..100) {
+            0..50 => Op::Insert(rng.random_range(0..MAX_VALUE)),
+            50..80 => Op::Remove(rng.random_range(0..MAX_VALUE)),
+            80..89 => Op::PopMin,
+            89..98 => Op::PopMax,
+            _ => Op::Clear,
+        })
+        .collect()
+}
+
+/// Apply `ops` to the tree and the model, describing the first disagreement.
+fn check(ops: &[Op]) -> Result<(), String> {
+    let mut tree = BinaryTree::new();
+    let mut model = BTreeSet::new();
+    for (step, op) in ops.iter().enumerate() {
+        let (actual, expected) = match *op {
+            Op::Insert(value) => {
+                let expected = !model.contains(&value);
+                tree.insert(value);
+                model.insert(value);
+                (expected.then_some(value), expected.then_some(value))
+            }
+            Op::Remove(value) => (tree.remove(&value), model.take(&value)),
+            Op::PopMin => (tree.pop_min(), model.pop_first()),
+            Op::PopMax => (tree.pop_max(), model.pop_last()),
+            Op::Clear => {
+                tree.clear();
+                model.clear();
+                (None, None)
+            }
+        };
+        let fail = |what: &str| Err(format!("step {step} ({op:?}): {what} differs"));
+        if actual != expected {
+            return fail("result");
+        }
+        if tree.len() != model.len() {
+            return fail("len");
+        }
+        if (0..MAX_VALUE).any(|value| tree.has(&value) != model.contains(&value)) {
+            return fail("has");
+        }
+        if !tree.iter().eq(model.iter()) {
+            return fail("in-order traversal");
+        }
+        if tree.first() != model.first() || tree.last() != model.last() {
+            return fail("first/last");
+        }
+        if !(0..tree.len()).all(|k| tree.select(k) == model.iter().nth(k)) {
+            return fail("select");
+        }
+    }
+    Ok(())
+}
+
+/// Shrink a failing sequence: drop chunks of operations, then single
+/// operations, then move values towards zero, as long as it keeps failing.
+fn shrink(mut ops: Vec<Op>, fails: impl Fn(&[Op]) -> bool) -> Vec<Op> {
+    let mut chunk = ops.len().max(1);
+    while chunk > 0 {
+        let mut start = 0;
+        while start < ops.len() {
+            let end = (start + chunk).min(ops.len());
+            let candidate = [&ops[..start], &ops[end..]].concat();
+            if fails(&candidate) {
+                ops = candidate;
+            } else {
+                start += chunk;
+            }
+        }
+        chunk /= 2;
+    }
+    // values usually matter in relation to each other, so lower a value in
+    // every operation at once before lowering single occurrences
+    let lower = |op: Op, from: Option<i32>| match op {
+        Op::Insert(value) if value > 0 && from.is_none_or(|from| from == value) => {
+            Some(Op::Insert(value - 1))
+        }
+        Op::Remove(value) if value > 0 && from.is_none_or(|from| from == value) => {
+            Some(Op::Remove(value - 1))
+        }
+        _ => None,
+    };
+    for value in (1..MAX_VALUE).rev() {
+        let candidate: Vec<Op> = ops
+            .iter()
+            .map(|op| lower(*op, Some(value)).unwrap_or(*op))
+            .collect();
+        if candidate != ops && fails(&candidate) {
+            ops = candidate;
+        }
+    }
+    for i in 0..ops.len() {
+        while let Some(smaller) = lower(ops[i], None) {
+            let mut candidate = ops.clone();
+            candidate[i] = smaller;
+            if !fails(&candidate) {
+                break;
+            }
+            ops = candidate;
+        }
+    }
+    ops
+}
+
+#[test]
+fn binary_tree_matches_btree_set() {
+    for seed in 0..CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let ops = random_ops(&mut rng);
+        if let Err(err) = check(&ops) {
+            let minimal = shrink(ops, |ops| check(ops).is_err());
+            panic!(
+                "seed {seed}: {err}\nminimal reproduction: {minimal:?}\n{}",
+                check(&minimal).unwrap_err()
+            );
+        }
+    }
+}
+
+#[test]
+fn shrink_finds_minimal_sequence() {
+    // a made-up bug: removing a value that is greater than 5 after it was
+    // inserted
+    let fails = |ops: &[Op]| {
+        ops.iter().enumerate().any(|(i, op)| match op {
+            Op::Remove(value) => *value > 5 && ops[..i].contains(&Op::Insert(*value)),
+            _ => false,
+        })
+    };
+    let ops = vec![
+        Op::Insert(3),
+        Op::Insert(30),
+        Op::PopMin,
+        Op::Insert(9),
+        Op::Clear,
+        Op::Remove(30),
+        Op::Remove(9),
+    ];
+    assert_eq!(shrink(ops, fails), [Op::Insert(6), Op::Remove(6)]);
+}
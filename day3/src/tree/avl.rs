@@ -0,0 +1,225 @@
+use std::cmp::Ordering;
+
+#[derive(Debug)]
+struct Node<T: Ord> {
+    value: T,
+    /// Number of nodes on the longest path from this node down to a leaf.
+    height: usize,
+    left: SubTree<T>,
+    right: SubTree<T>,
+}
+
+#[derive(Debug)]
+struct SubTree<T: Ord>(Option<Box<Node<T>>>);
+
+/// A self-balancing (AVL) variant of [`BinaryTree`](super::BinaryTree).
+///
+/// After every insertion the heights of the two children of any node differ
+/// by at most one, so the tree height stays logarithmic even for sorted input.
+#[derive(Debug)]
+pub struct AvlTree<T: Ord> {
+    root: SubTree<T>,
+}
+
+impl<T: Ord> AvlTree<T> {
+    pub fn new() -> Self {
+        Self {
+            root: SubTree(None),
+        }
+    }
+
+    pub fn insert(&mut self, value: T) {
+        self.root.insert(value);
+    }
+
+    pub fn has(&self, value: &T) -> bool {
+        self.root.has(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.0.is_none()
+    }
+
+    /// Height of the tree: 0 when empty, 1 for a single node.
+    pub fn height(&self) -> usize {
+        self.root.height()
+    }
+}
+
+impl<T: Ord> Default for AvlTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> SubTree<T> {
+    fn insert(&mut self, value: T) {
+        match self {
+            SubTree(None) => {
+                *self = SubTree(Some(Box::new(Node {
+                    value,
+                    height: 1,
+                    left: SubTree(None),
+                    right: SubTree(None),
+                })));
+                return;
+            }
+            SubTree(Some(node)) => match value.cmp(&node.value) {
+                Ordering::Less => node.left.insert(value),
+                Ordering::Greater => node.right.insert(value),
+                Ordering::Equal => return,
+            },
+        }
+        self.rebalance();
+    }
+
+    fn has(&self, value: &T) -> bool {
+        match self {
+            SubTree(None) => false,
+            SubTree(Some(node)) => match value.cmp(&node.value) {
+                Ordering::Less => node.left.has(value),
+                Ordering::Greater => node.right.has(value),
+                Ordering::Equal => true,
+            },
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            SubTree(None) => 0,
+            SubTree(Some(node)) => 1 + node.left.len() + node.right.len(),
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.0.as_ref().map_or(0, |node| node.height)
+    }
+
+    /// Height of the left subtree minus height of the right subtree.
+    fn balance_factor(&self) -> isize {
+        match self {
+            SubTree(None) => 0,
+            SubTree(Some(node)) => node.left.height() as isize - node.right.height() as isize,
+        }
+    }
+
+    fn update_height(&mut self) {
+        if let SubTree(Some(node)) = self {
+            node.height = 1 + node.left.height().max(node.right.height());
+        }
+    }
+
+    /// Restore the AVL invariant at this node, assuming both children
+    /// already satisfy it.
+    fn rebalance(&mut self) {
+        self.update_height();
+        let balance = self.balance_factor();
+        let Some(node) = self.0.as_mut() else {
+            return;
+        };
+        match balance {
+            // left heavy
+            2 => {
+                if node.left.balance_factor() < 0 {
+                    node.left.rotate_left();
+                }
+                self.rotate_right();
+            }
+            // right heavy
+            -2 => {
+                if node.right.balance_factor() > 0 {
+                    node.right.rotate_right();
+                }
+                self.rotate_left();
+            }
+            _ => {}
+        }
+    }
+
+    //     y            x
+    //    / \          / \
+    //   x   c  --->  a   y
+    //  / \              / \
+    // a   b            b   c
+    fn rotate_right(&mut self) {
+        let Some(mut y) = self.0.take() else {
+            return;
+        };
+        let Some(mut x) = y.left.0.take() else {
+            self.0 = Some(y);
+            return;
+        };
+        y.left = std::mem::replace(&mut x.right, SubTree(None));
+        let mut y = SubTree(Some(y));
+        y.update_height();
+        x.right = y;
+        *self = SubTree(Some(x));
+        self.update_height();
+    }
+
+    //   x                y
+    //  / \              / \
+    // a   y    --->    x   c
+    //    / \          / \
+    //   b   c        a   b
+    fn rotate_left(&mut self) {
+        let Some(mut x) = self.0.take() else {
+            return;
+        };
+        let Some(mut y) = x.right.0.take() else {
+            self.0 = Some(x);
+            return;
+        };
+        x.right = std::mem::replace(&mut y.left, SubTree(None));
+        let mut x = SubTree(Some(x));
+        x.update_height();
+        y.left = x;
+        *self = SubTree(Some(y));
+        self.update_height();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_api_as_binary_tree() {
+        let mut tree = AvlTree::new();
+        assert_eq!(tree.len(), 0);
+        tree.insert(2);
+        tree.insert(1);
+        tree.insert(2); // not a unique item
+        tree.insert(3);
+        assert_eq!(tree.len(), 3);
+        assert!(tree.has(&1) && tree.has(&2) && tree.has(&3));
+        assert!(!tree.has(&4));
+        assert_eq!(tree.height(), 2);
+    }
+
+    #[test]
+    fn sorted_inserts_stay_logarithmic() {
+        let n = 100_000;
+        let mut ascending = AvlTree::new();
+        let mut descending = AvlTree::new();
+        for i in 0..n {
+            ascending.insert(i);
+            descending.insert(n - i);
+        }
+        assert_eq!(ascending.len(), n);
+        assert_eq!(descending.len(), n);
+        // an AVL tree with n nodes is never taller than 1.44 * log2(n + 2)
+        let bound = (1.44 * ((n + 2) as f64).log2()) as usize;
+        assert!(ascending.height() <= bound, "height {}", ascending.height());
+        assert!(
+            descending.height() <= bound,
+            "height {}",
+            descending.height()
+        );
+        assert!(ascending.has(&0) && ascending.has(&(n - 1)));
+    }
+}
@@ -0,0 +1,101 @@
+use super::{BinaryTree, Node, SubTree};
+
+impl<T: Ord> BinaryTree<T> {
+    /// Build a perfectly balanced tree in O(n) from values that are already
+    /// in strictly ascending order (sorted and without duplicates). Values
+    /// that are not are sorted and deduplicated first, in O(n log n).
+    pub fn from_sorted<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut values: Vec<T> = values.into_iter().collect();
+        if !values.windows(2).all(|pair| pair[0] < pair[1]) {
+            values.sort();
+            values.dedup();
+        }
+        let len = values.len();
+        Self {
+            root: SubTree::from_sorted(&mut values.into_iter(), len),
+        }
+    }
+}
+
+impl<T: Ord> SubTree<T> {
+    /// Build a balanced subtree from the next `len` values of `values`,
+    /// consuming them in order: left subtree, then root, then right subtree.
+    /// The recursion depth is the height of the result, i.e. logarithmic.
+    fn from_sorted(values: &mut impl Iterator<Item = T>, len: usize) -> Self {
+        if len == 0 {
+            return SubTree(None);
+        }
+        let left_len = len / 2;
+        let left = SubTree::from_sorted(values, left_len);
+        let value = values.next().expect("enough sorted values");
+        let right = SubTree::from_sorted(values, len - left_len - 1);
+        SubTree(Some(Box::new(Node {
+            value,
+            size: len,
+            left,
+            right,
+        })))
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryTree<T> {
+    /// Collect the values into a balanced tree, whatever their order.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted(iter)
+    }
+}
+
+impl<T: Ord> Extend<T> for BinaryTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn height<T: Ord>(subtree: &SubTree<T>) -> usize {
+        match subtree {
+            SubTree(None) => 0,
+            SubTree(Some(node)) => 1 + height(&node.left).max(height(&node.right)),
+        }
+    }
+
+    #[test]
+    fn from_sorted_is_balanced() {
+        let tree = BinaryTree::from_sorted(0..100_000);
+        assert_eq!(tree.len(), 100_000);
+        // 2^17 > 100_000
+        assert_eq!(height(&tree.root), 17);
+        assert_eq!(tree.select(50_000), Some(&50_000));
+        assert!(tree.iter().copied().eq(0..100_000));
+
+        let empty = BinaryTree::<i32>::from_sorted([]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn from_sorted_fixes_unsorted_input() {
+        let tree = BinaryTree::from_sorted([3, 1, 2, 3, 0]);
+        assert_eq!(tree.len(), 4);
+        assert_eq!(height(&tree.root), 3);
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert!(tree.has(&3));
+        assert_eq!(tree.rank(&2), 2);
+    }
+
+    #[test]
+    fn collect_and_extend() {
+        let mut tree: BinaryTree<i32> = [5, 1, 4, 1, 3, 5, 2].into_iter().collect();
+        assert_eq!(tree.len(), 5);
+        assert_eq!(height(&tree.root), 3);
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+        tree.extend([0, 6, 3]);
+        assert_eq!(tree.len(), 7);
+        assert_eq!(tree.rank(&6), 6);
+    }
+}
@@ -0,0 +1,302 @@
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// Every link between nodes has its own lock. Nodes are reference counted so
+// that a thread can keep a node alive while it holds the lock on one of the
+// node's links, even if another thread unlinks the node from its parent.
+type Link<T> = Option<Arc<Node<T>>>;
+
+#[derive(Debug)]
+struct Node<T: Ord> {
+    value: T,
+    left: Mutex<Link<T>>,
+    right: Mutex<Link<T>>,
+}
+
+/// An ordered set that can be shared between threads, e.g. in an `Arc`, and
+/// modified through `&self`.
+///
+/// Rather than one lock around the whole tree, as `Arc<Mutex<BinaryTree<T>>>`
+/// would have, every link is locked separately. Operations walk down the tree
+/// hand over hand: the lock on a child link is taken before the lock on the
+/// parent link is released, so threads working in different subtrees do not
+/// wait for each other. Locks are always taken top-down, which rules out
+/// deadlocks.
+///
+/// Like [`BinaryTree`](super::BinaryTree) the tree does not rebalance, and the
+/// walks recurse once per level.
+#[derive(Debug)]
+pub struct ConcurrentTree<T: Ord> {
+    root: Mutex<Link<T>>,
+    len: AtomicUsize,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap()
+}
+
+impl<T: Ord> ConcurrentTree<T> {
+    pub fn new() -> Self {
+        Self {
+            root: Mutex::new(None),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Insert `value`, returning whether it was not already present.
+    pub fn insert(&self, value: T) -> bool {
+        let inserted = insert_at(lock(&self.root), value);
+        if inserted {
+            self.len.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+        inserted
+    }
+
+    pub fn has(&self, value: &T) -> bool {
+        has_at(lock(&self.root), value)
+    }
+
+    /// Remove `value`, returning whether it was present.
+    pub fn remove(&self, value: &T) -> bool {
+        let removed = remove_at(lock(&self.root), value);
+        if removed {
+            self.len.fetch_sub(1, AtomicOrdering::Relaxed);
+        }
+        removed
+    }
+
+    /// The number of values, which may already be stale if other threads are
+    /// modifying the tree.
+    pub fn len(&self) -> usize {
+        self.len.load(AtomicOrdering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Ord> Default for ConcurrentTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Drop for ConcurrentTree<T> {
+    fn drop(&mut self) {
+        // nobody else can hold a node any more, so unlink them one by one
+        // instead of letting the drop glue recurse
+        let mut stack: Vec<Arc<Node<T>>> = Vec::new();
+        if let Ok(root) = self.root.get_mut() {
+            stack.extend(root.take());
+        }
+        while let Some(node) = stack.pop() {
+            if let Ok(mut node) = Arc::try_unwrap(node) {
+                for link in [&mut node.left, &mut node.right] {
+                    if let Ok(link) = link.get_mut() {
+                        stack.extend(link.take());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The child link of `node` on the side of `value`, or `None` if the node
+/// holds `value` itself.
+fn child<'a, T: Ord>(node: &'a Node<T>, value: &T) -> Option<&'a Mutex<Link<T>>> {
+    match value.cmp(&node.value) {
+        Ordering::Less => Some(&node.left),
+        Ordering::Greater => Some(&node.right),
+        Ordering::Equal => None,
+    }
+}
+
+// Each of the walks below is called with the lock on a link held. Before
+// descending it clones the `Arc` of the node behind the link, so that the
+// guard on the child link, which borrows from the node, stays valid after the
+// parent guard is dropped.
+
+fn insert_at<T: Ord>(mut guard: MutexGuard<'_, Link<T>>, value: T) -> bool {
+    let node = match &*guard {
+        Some(node) => Arc::clone(node),
+        None => {
+            *guard = Some(Arc::new(Node {
+                value,
+                left: Mutex::new(None),
+                right: Mutex::new(None),
+            }));
+            return true;
+        }
+    };
+    let Some(child) = child(&node, &value) else {
+        return false;
+    };
+    let child = lock(child);
+    drop(guard);
+    insert_at(child, value)
+}
+
+fn has_at<T: Ord>(guard: MutexGuard<'_, Link<T>>, value: &T) -> bool {
+    let Some(node) = guard.as_ref().map(Arc::clone) else {
+        return false;
+    };
+    let Some(child) = child(&node, value) else {
+        return true;
+    };
+    let child = lock(child);
+    drop(guard);
+    has_at(child, value)
+}
+
+fn remove_at<T: Ord>(mut guard: MutexGuard<'_, Link<T>>, value: &T) -> bool {
+    let Some(node) = guard.as_ref().map(Arc::clone) else {
+        return false;
+    };
+    if let Some(child) = child(&node, value) {
+        let child = lock(child);
+        drop(guard);
+        return remove_at(child, value);
+    }
+    // Holding the link to `node` keeps every other thread from entering its
+    // subtrees, and threads that are already inside have moved past the
+    // child links once we manage to lock them.
+    let replacement = if lock(&node.left).is_none() {
+        lock(&node.right).take()
+    } else if lock(&node.right).is_none() {
+        lock(&node.left).take()
+    } else {
+        // two children: the in-order successor node is moved into the place
+        // of the removed node
+        let successor = pop_min_at(lock(&node.right));
+        *lock(&successor.left) = lock(&node.left).take();
+        *lock(&successor.right) = lock(&node.right).take();
+        Some(successor)
+    };
+    *guard = replacement;
+    true
+}
+
+/// Unlink the node with the smallest value below a non-empty link and return
+/// it, with both of its links empty.
+fn pop_min_at<T: Ord>(mut guard: MutexGuard<'_, Link<T>>) -> Arc<Node<T>> {
+    let node = Arc::clone(guard.as_ref().expect("non-empty link"));
+    let left = lock(&node.left);
+    if left.is_some() {
+        drop(guard);
+        return pop_min_at(left);
+    }
+    drop(left);
+    *guard = lock(&node.right).take();
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::BinaryTree;
+    use std::thread;
+    use std::time::Instant;
+
+    const THREADS: usize = 8;
+    const PER_THREAD: usize = 20_000;
+
+    /// A scrambled but collision-free sequence, so that the tree stays
+    /// reasonably shallow and every thread works on its own values.
+    fn value(thread: usize, i: usize) -> u64 {
+        ((thread * PER_THREAD + i) as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 16
+    }
+
+    #[test]
+    fn single_threaded_set_semantics() {
+        let tree = ConcurrentTree::new();
+        for v in [50, 30, 70, 20, 40, 60, 80, 35, 45] {
+            assert!(tree.insert(v));
+        }
+        assert!(!tree.insert(40));
+        assert_eq!(tree.len(), 9);
+        // leaf, one child, two children (successor is a direct child), root
+        assert!(tree.remove(&20));
+        assert!(tree.remove(&30));
+        assert!(tree.remove(&70));
+        assert!(tree.remove(&50));
+        assert!(!tree.remove(&50));
+        assert_eq!(tree.len(), 5);
+        for v in [35, 40, 45, 60, 80] {
+            assert!(tree.has(&v));
+        }
+        for v in [20, 30, 50, 70] {
+            assert!(!tree.has(&v));
+        }
+    }
+
+    /// Many threads at once, each inserting its own values, checking them
+    /// and removing every other one.
+    fn hammer_per_link(tree: &Arc<ConcurrentTree<u64>>) {
+        let mut handles = Vec::new();
+        for t in 0..THREADS {
+            let tree_clone = Arc::clone(tree);
+            handles.push(thread::spawn(move || {
+                for i in 0..PER_THREAD {
+                    assert!(tree_clone.insert(value(t, i)));
+                }
+                for i in 0..PER_THREAD {
+                    assert!(tree_clone.has(&value(t, i)));
+                    if i % 2 == 0 {
+                        assert!(tree_clone.remove(&value(t, i)));
+                    }
+                }
+            }));
+        }
+        handles.into_iter().for_each(|h| h.join().unwrap());
+    }
+
+    /// The workload of [`hammer_per_link`] behind one big lock.
+    fn hammer_single_lock(tree: &Arc<Mutex<BinaryTree<u64>>>) {
+        let mut handles = Vec::new();
+        for t in 0..THREADS {
+            let tree_clone = Arc::clone(tree);
+            handles.push(thread::spawn(move || {
+                for i in 0..PER_THREAD {
+                    tree_clone.lock().unwrap().insert(value(t, i));
+                }
+                for i in 0..PER_THREAD {
+                    assert!(tree_clone.lock().unwrap().has(&value(t, i)));
+                    if i % 2 == 0 {
+                        tree_clone.lock().unwrap().remove(&value(t, i));
+                    }
+                }
+            }));
+        }
+        handles.into_iter().for_each(|h| h.join().unwrap());
+    }
+
+    #[test]
+    fn stress() {
+        let tree = Arc::new(ConcurrentTree::new());
+        hammer_per_link(&tree);
+        assert_eq!(tree.len(), THREADS * PER_THREAD / 2);
+        for t in 0..THREADS {
+            for i in 0..PER_THREAD {
+                assert_eq!(tree.has(&value(t, i)), i % 2 == 1);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "reports timings; run with --release --ignored --nocapture"]
+    fn throughput_against_single_lock() {
+        let tree = Arc::new(ConcurrentTree::new());
+        let start = Instant::now();
+        hammer_per_link(&tree);
+        let per_link = start.elapsed();
+
+        let tree = Arc::new(Mutex::new(BinaryTree::new()));
+        let start = Instant::now();
+        hammer_single_lock(&tree);
+        let single_lock = start.elapsed();
+
+        println!("lock per link: {per_link:?}, single lock: {single_lock:?}");
+    }
+}
@@ -0,0 +1,155 @@
+use std::io::{self, Read, Write};
+
+use super::{BinaryTree, Node, SubTree};
+use crate::codec::{self, Decode, DecodeError, Encode, Kind};
+
+/// The values are stored in pre-order, each followed by the size of its left
+/// subtree as a `u64`, which is enough to restore the exact shape of the tree.
+impl<T: Ord + Encode> Encode for BinaryTree<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        codec::write_header(writer, Kind::BinaryTree, self.len())?;
+        let mut stack: Vec<&Node<T>> = self.root.0.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            node.value.encode(writer)?;
+            (node.left.size() as u64).encode(writer)?;
+            if let SubTree(Some(right)) = &node.right {
+                stack.push(right);
+            }
+            if let SubTree(Some(left)) = &node.left {
+                stack.push(left);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Ord + Decode> Decode for BinaryTree<T> {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self, DecodeError> {
+        let len = codec::read_header(reader, Kind::BinaryTree)?;
+        let mut values = Vec::new();
+        let mut left_sizes = Vec::new();
+        for _ in 0..len {
+            values.push(Some(T::decode(reader)?));
+            left_sizes.push(u64::decode(reader)?);
+        }
+        let len = values.len();
+
+        // In pre-order the left child of node `i` is node `i + 1` and the
+        // right child follows the left subtree, so the size of every subtree
+        // can be worked out front to back.
+        let mut sizes = vec![0; len];
+        if len > 0 {
+            sizes[0] = len;
+        }
+        for i in 0..len {
+            let left_size = usize::try_from(left_sizes[i])
+                .ok()
+                .filter(|left_size| *left_size < sizes[i])
+                .ok_or(DecodeError::Corrupt("left subtree is too large"))?;
+            let right_size = sizes[i] - 1 - left_size;
+            if left_size > 0 {
+                sizes[i + 1] = left_size;
+            }
+            if right_size > 0 {
+                sizes[i + 1 + left_size] = right_size;
+            }
+        }
+
+        // build the nodes back to front, so that both children of a node are
+        // ready by the time the node is
+        let mut subtrees: Vec<SubTree<T>> = (0..len).map(|_| SubTree(None)).collect();
+        for i in (0..len).rev() {
+            let left_size = left_sizes[i] as usize;
+            let right_size = sizes[i] - 1 - left_size;
+            let mut child = |size: usize, at: usize| match size {
+                0 => SubTree(None),
+                _ => SubTree(subtrees[at].0.take()),
+            };
+            let left = child(left_size, i + 1);
+            let right = child(right_size, i + 1 + left_size);
+            subtrees[i] = SubTree(Some(Box::new(Node {
+                value: values[i].take().expect("each value is used once"),
+                size: sizes[i],
+                left,
+                right,
+            })));
+        }
+        let tree = BinaryTree {
+            root: subtrees.into_iter().next().unwrap_or(SubTree(None)),
+        };
+
+        let mut values = tree.iter();
+        let mut previous = values.next();
+        for value in values {
+            if previous.is_some_and(|previous| previous >= value) {
+                return Err(DecodeError::Corrupt("values are out of order"));
+            }
+            previous = Some(value);
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::tests::tree_of;
+
+    fn encoded(tree: &BinaryTree<i32>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        tree.encode(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn round_trip_keeps_shape() {
+        for values in [
+            &[][..],
+            &[1],
+            &[4, 2, 6, 1, 3, 5, 7],
+            &[1, 2, 3, 4],
+            &[5, 1, 4, 2, 3],
+        ] {
+            let tree = tree_of(values);
+            let bytes = encoded(&tree);
+            let decoded = BinaryTree::<i32>::decode(&mut bytes.as_slice()).unwrap();
+            assert_eq!(decoded.to_string(), tree.to_string());
+            assert_eq!(decoded.len(), tree.len());
+            assert_eq!(decoded.select(0), tree.first());
+        }
+
+        let mut words = BinaryTree::new();
+        for word in ["log", "serde", "base64"] {
+            words.insert(String::from(word));
+        }
+        let mut bytes = Vec::new();
+        words.encode(&mut bytes).unwrap();
+        let decoded = BinaryTree::<String>::decode(&mut bytes.as_slice()).unwrap();
+        assert!(decoded.iter().eq(words.iter()));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let bytes = encoded(&tree_of(&[2, 1, 3]));
+        for end in 0..bytes.len() {
+            assert!(matches!(
+                BinaryTree::<i32>::decode(&mut &bytes[..end]),
+                Err(DecodeError::Truncated)
+            ));
+        }
+
+        // header (14 bytes), then (value: i32, left size: u64) per node
+        let mut corrupt = bytes.clone();
+        corrupt[18] = 7;
+        assert!(matches!(
+            BinaryTree::<i32>::decode(&mut corrupt.as_slice()),
+            Err(DecodeError::Corrupt("left subtree is too large"))
+        ));
+        let mut corrupt = bytes.clone();
+        corrupt[26] = 9;
+        assert!(matches!(
+            BinaryTree::<i32>::decode(&mut corrupt.as_slice()),
+            Err(DecodeError::Corrupt("values are out of order"))
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+
+use super::{BinaryTree, Node, SubTree};
+
+/// In-order (sorted) iterator over a [`BinaryTree`], created by [`BinaryTree::iter`].
+pub struct Iter<'a, T: Ord> {
+    // nodes whose value has not been yielded yet; the top is the next one
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T: Ord> Iter<'a, T> {
+    fn new(root: &'a SubTree<T>) -> Self {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    fn push_left_spine(&mut self, mut subtree: &'a SubTree<T>) {
+        while let SubTree(Some(node)) = subtree {
+            self.stack.push(node);
+            subtree = &node.left;
+        }
+    }
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // everything in the right subtree comes before the remaining ancestors
+        self.push_left_spine(&node.right);
+        Some(&node.value)
+    }
+}
+
+/// Pre-order (node, left, right) iterator, created by [`BinaryTree::iter_preorder`].
+pub struct PreOrder<'a, T: Ord> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T: Ord> Iterator for PreOrder<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // push right first so that the left subtree is visited first
+        if let SubTree(Some(right)) = &node.right {
+            self.stack.push(right);
+        }
+        if let SubTree(Some(left)) = &node.left {
+            self.stack.push(left);
+        }
+        Some(&node.value)
+    }
+}
+
+/// Post-order (left, right, node) iterator, created by [`BinaryTree::iter_postorder`].
+pub struct PostOrder<'a, T: Ord> {
+    // the flag records whether the children of the node were already pushed
+    stack: Vec<(&'a Node<T>, bool)>,
+}
+
+impl<'a, T: Ord> Iterator for PostOrder<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(&node.value);
+            }
+            self.stack.push((node, true));
+            if let SubTree(Some(right)) = &node.right {
+                self.stack.push((right, false));
+            }
+            if let SubTree(Some(left)) = &node.left {
+                self.stack.push((left, false));
+            }
+        }
+    }
+}
+
+/// Breadth-first iterator, created by [`BinaryTree::iter_level_order`].
+pub struct LevelOrder<'a, T: Ord> {
+    queue: VecDeque<&'a Node<T>>,
+}
+
+impl<'a, T: Ord> Iterator for LevelOrder<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        if let SubTree(Some(left)) = &node.left {
+            self.queue.push_back(left);
+        }
+        if let SubTree(Some(right)) = &node.right {
+            self.queue.push_back(right);
+        }
+        Some(&node.value)
+    }
+}
+
+/// Owning in-order iterator, created by calling `into_iter` on a [`BinaryTree`].
+pub struct IntoIter<T: Ord> {
+    stack: Vec<Box<Node<T>>>,
+}
+
+impl<T: Ord> IntoIter<T> {
+    fn push_left_spine(&mut self, mut subtree: SubTree<T>) {
+        while let Some(mut node) = subtree.0.take() {
+            subtree = SubTree(node.left.0.take());
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<T: Ord> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        self.push_left_spine(SubTree(node.right.0.take()));
+        Some(node.value)
+    }
+}
+
+impl<T: Ord> BinaryTree<T> {
+    /// Iterate over the values in ascending order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.root)
+    }
+
+    /// Iterate over the values visiting each node before its children.
+    pub fn iter_preorder(&self) -> PreOrder<'_, T> {
+        PreOrder {
+            stack: self.root.0.as_deref().into_iter().collect(),
+        }
+    }
+
+    /// Iterate over the values visiting each node after its children.
+    pub fn iter_postorder(&self) -> PostOrder<'_, T> {
+        PostOrder {
+            stack: self
+                .root
+                .0
+                .as_deref()
+                .into_iter()
+                .map(|node| (node, false))
+                .collect(),
+        }
+    }
+
+    /// Iterate over the values one depth level at a time, left to right.
+    pub fn iter_level_order(&self) -> LevelOrder<'_, T> {
+        LevelOrder {
+            queue: self.root.0.as_deref().into_iter().collect(),
+        }
+    }
+}
+
+impl<T: Ord> IntoIterator for BinaryTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut iter = IntoIter { stack: Vec::new() };
+        iter.push_left_spine(SubTree(self.root.0.take()));
+        iter
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a BinaryTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::tests::tree_of;
+
+    //       4
+    //     /   \
+    //    2     6
+    //   / \   / \
+    //  1   3 5   7
+    const SAMPLE: [i32; 7] = [4, 2, 6, 1, 3, 5, 7];
+
+    #[test]
+    fn traversal_orders() {
+        let tree = tree_of(&SAMPLE);
+        assert_eq!(
+            tree.iter().copied().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 6, 7]
+        );
+        assert_eq!(
+            tree.iter_preorder().copied().collect::<Vec<_>>(),
+            [4, 2, 1, 3, 6, 5, 7]
+        );
+        assert_eq!(
+            tree.iter_postorder().copied().collect::<Vec<_>>(),
+            [1, 3, 2, 5, 7, 6, 4]
+        );
+        assert_eq!(
+            tree.iter_level_order().copied().collect::<Vec<_>>(),
+            [4, 2, 6, 1, 3, 5, 7]
+        );
+    }
+
+    #[test]
+    fn into_iterator() {
+        let tree = tree_of(&SAMPLE);
+        let mut sum = 0;
+        for value in &tree {
+            sum += value;
+        }
+        assert_eq!(sum, 28);
+        assert_eq!(tree.into_iter().collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6, 7]);
+
+        let empty: BinaryTree<i32> = BinaryTree::new();
+        assert_eq!(empty.iter().next(), None);
+        assert_eq!(empty.into_iter().next(), None);
+    }
+}
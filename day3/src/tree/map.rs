@@ -0,0 +1,437 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+
+#[derive(Debug)]
+struct Node<K: Ord, V> {
+    key: K,
+    value: V,
+    left: SubTree<K, V>,
+    right: SubTree<K, V>,
+}
+
+#[derive(Debug)]
+struct SubTree<K: Ord, V>(Option<Box<Node<K, V>>>);
+
+/// An ordered map, the key-value counterpart of [`BinaryTree`](super::BinaryTree).
+///
+/// The method names follow `HashMap`, so the two can be swapped for each other.
+#[derive(Debug)]
+pub struct BinaryTreeMap<K: Ord, V> {
+    root: SubTree<K, V>,
+    len: usize,
+}
+
+impl<K: Ord, V> BinaryTreeMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            root: SubTree(None),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a key-value pair, returning the previous value of the key if
+    /// there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut subtree = &self.root;
+        while let SubTree(Some(node)) = subtree {
+            subtree = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut subtree = &mut self.root;
+        while let SubTree(Some(node)) = subtree {
+            subtree = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Remove a key from the map, returning its value if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (_, value) = self.root.find_slot(key).remove_root()?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Get the entry for `key`, for in-place insertion or update.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let len = &mut self.len;
+        let slot = self.root.find_slot(&key);
+        if slot.0.is_some() {
+            Entry::Occupied(OccupiedEntry { slot, len })
+        } else {
+            Entry::Vacant(VacantEntry { key, slot, len })
+        }
+    }
+
+    /// Iterate over the key-value pairs in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+impl<K: Ord, V> Default for BinaryTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Like the walks of `BinaryTree`, these loop instead of recursing, so a
+// degenerate map (e.g. built from sorted keys) cannot overflow the stack.
+impl<K: Ord, V> SubTree<K, V> {
+    /// Compare `key` against the key at the root of this subtree, or `None`
+    /// if the subtree is empty.
+    fn cmp_root<Q>(&self, key: &Q) -> Option<Ordering>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.0.as_ref().map(|node| key.cmp(node.key.borrow()))
+    }
+
+    /// Find the subtree whose root holds `key`, or the empty subtree where
+    /// `key` would be inserted.
+    fn find_slot<Q>(&mut self, key: &Q) -> &mut SubTree<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut subtree = self;
+        while let Some(ordering) = subtree.cmp_root(key) {
+            if ordering == Ordering::Equal {
+                break;
+            }
+            let node = subtree.0.as_mut().expect("non-empty subtree");
+            subtree = if ordering == Ordering::Less {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+        subtree
+    }
+
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        let mut subtree = self;
+        while subtree.0.as_ref()?.left.0.is_some() {
+            subtree = &mut subtree.0.as_mut().expect("non-empty subtree").left;
+        }
+        subtree.remove_root()
+    }
+
+    /// Unlink the node at the root of this subtree and return its entry.
+    fn remove_root(&mut self) -> Option<(K, V)> {
+        let node = self.0.as_mut()?;
+        if node.left.0.is_some() && node.right.0.is_some() {
+            // two children: the in-order successor takes the place of the
+            // removed entry
+            let (key, value) = node.right.pop_min()?;
+            let key = std::mem::replace(&mut node.key, key);
+            let value = std::mem::replace(&mut node.value, value);
+            return Some((key, value));
+        }
+        let Node {
+            key,
+            value,
+            left,
+            right,
+        } = *self.0.take()?;
+        *self = if left.0.is_some() { left } else { right };
+        Some((key, value))
+    }
+}
+
+impl<K: Ord, V> Drop for SubTree<K, V> {
+    fn drop(&mut self) {
+        // detach the children before each node is freed, so that dropping a
+        // node never recurses into its subtrees
+        let mut stack: Vec<Box<Node<K, V>>> = self.0.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.0.take());
+            stack.extend(node.right.0.take());
+        }
+    }
+}
+
+/// A view into a single entry of a [`BinaryTreeMap`], created by
+/// [`BinaryTreeMap::entry`].
+pub enum Entry<'a, K: Ord, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K: Ord, V> {
+    // always a non-empty subtree
+    slot: &'a mut SubTree<K, V>,
+    len: &'a mut usize,
+}
+
+pub struct VacantEntry<'a, K: Ord, V> {
+    key: K,
+    // always an empty subtree
+    slot: &'a mut SubTree<K, V>,
+    len: &'a mut usize,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Insert `default` if the entry is vacant, and return the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Modify the value in place if the entry is occupied.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    fn node(&self) -> &Node<K, V> {
+        self.slot.0.as_ref().expect("occupied entry has a node")
+    }
+
+    fn node_mut(&mut self) -> &mut Node<K, V> {
+        self.slot.0.as_mut().expect("occupied entry has a node")
+    }
+
+    pub fn key(&self) -> &K {
+        &self.node().key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.node().value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.node_mut().value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self
+            .slot
+            .0
+            .as_mut()
+            .expect("occupied entry has a node")
+            .value
+    }
+
+    /// Replace the value of the entry, returning the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the entry from the map, returning its value.
+    pub fn remove(self) -> V {
+        *self.len -= 1;
+        let (_, value) = self.slot.remove_root().expect("occupied entry has a node");
+        value
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Insert the value into the map, returning a reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.len += 1;
+        let node = self.slot.0.insert(Box::new(Node {
+            key: self.key,
+            value,
+            left: SubTree(None),
+            right: SubTree(None),
+        }));
+        &mut node.value
+    }
+}
+
+/// In-order iterator over a [`BinaryTreeMap`], created by [`BinaryTreeMap::iter`].
+pub struct Iter<'a, K: Ord, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K: Ord, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut subtree: &'a SubTree<K, V>) {
+        while let SubTree(Some(node)) = subtree {
+            self.stack.push(node);
+            subtree = &node.left;
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a BinaryTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::on_small_stack;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map = BinaryTreeMap::new();
+        assert_eq!(map.insert(3, "c"), None);
+        assert_eq!(map.insert(1, "a"), None);
+        assert_eq!(map.insert(2, "b"), None);
+        assert_eq!(map.insert(3, "C"), Some("c"));
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(&3), Some(&"C"));
+        if let Some(value) = map.get_mut(&1) {
+            *value = "A";
+        }
+        assert_eq!(map.get(&1), Some(&"A"));
+        assert_eq!(map.remove(&3), Some("C"));
+        assert_eq!(map.remove(&3), None);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&1, &"A"), (&2, &"b")]);
+    }
+
+    #[test]
+    fn entry_like_hash_map() {
+        let mut page_counts = BinaryTreeMap::new();
+        page_counts.insert("Adventures of Huckleberry Finn", 207);
+        page_counts.insert("Grimms' Fairy Tales", 751);
+        page_counts.insert("Pride and Prejudice", 303);
+        assert!(!page_counts.contains_key("Les Misérables"));
+
+        for book in ["Pride and Prejudice", "Alice's Adventure in Wonderland"] {
+            let page_count: &mut i32 = page_counts.entry(book).or_insert(0);
+            *page_count += 1;
+        }
+        assert_eq!(page_counts.get("Pride and Prejudice"), Some(&304));
+        assert_eq!(page_counts.get("Alice's Adventure in Wonderland"), Some(&1));
+        assert_eq!(page_counts.len(), 4);
+
+        page_counts
+            .entry("Grimms' Fairy Tales")
+            .and_modify(|count| *count *= 2)
+            .or_insert(0);
+        assert_eq!(page_counts.get("Grimms' Fairy Tales"), Some(&1502));
+
+        if let Entry::Occupied(entry) = page_counts.entry("Grimms' Fairy Tales") {
+            assert_eq!(entry.remove(), 1502);
+        }
+        assert_eq!(page_counts.len(), 3);
+    }
+
+    #[test]
+    fn remove_entry_with_two_children() {
+        let mut map = BinaryTreeMap::new();
+        for key in [50, 30, 70, 20, 40, 60, 80, 65] {
+            map.insert(key, key * 10);
+        }
+        // the successor 60 has a right child of its own
+        assert_eq!(map.remove(&50), Some(500));
+        assert_eq!(map.remove(&70), Some(700));
+        assert_eq!(map.len(), 6);
+        assert_eq!(
+            map.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+            [20, 30, 40, 60, 65, 80]
+        );
+        assert_eq!(map.get(&65), Some(&650));
+    }
+
+    #[test]
+    fn degenerate_map_on_small_stack() {
+        on_small_stack(|| {
+            const N: u32 = 5_000;
+            let mut map = BinaryTreeMap::new();
+            for key in 0..N {
+                map.insert(key, ());
+            }
+            assert!(map.contains_key(&(N - 1)));
+            assert_eq!(map.remove(&0), Some(()));
+            assert_eq!(map.remove(&(N - 1)), Some(()));
+            assert_eq!(map.len(), N as usize - 2);
+            drop(map);
+        });
+    }
+}
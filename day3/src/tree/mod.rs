@@ -0,0 +1,286 @@
+pub mod avl;
+pub mod build;
+pub mod concurrent;
+pub mod encode;
+pub mod iter;
+pub mod map;
+pub mod persistent;
+pub mod query;
+pub mod render;
+pub mod set;
+
+use std::cmp::Ordering;
+
+#[derive(Debug)]
+struct Node<T: Ord> {
+    value: T,
+    /// Number of nodes in the subtree rooted at this node, itself included.
+    size: usize,
+    left: SubTree<T>,
+    right: SubTree<T>,
+}
+
+#[derive(Debug)]
+struct SubTree<T: Ord>(Option<Box<Node<T>>>);
+
+/// An ordered set of unique values, stored as an (unbalanced) binary search tree.
+#[derive(Debug)]
+pub struct BinaryTree<T: Ord> {
+    root: SubTree<T>,
+}
+
+impl<T: Ord> BinaryTree<T> {
+    pub fn new() -> Self {
+        Self {
+            root: SubTree(None),
+        }
+    }
+
+    pub fn insert(&mut self, value: T) {
+        self.root.insert(value);
+    }
+
+    pub fn has(&self, value: &T) -> bool {
+        self.root.has(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.0.is_none()
+    }
+
+    /// Remove `value` from the tree, returning it if it was present.
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        self.root.remove(value)
+    }
+
+    /// Remove and return the smallest value in the tree.
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.root.pop_min()
+    }
+
+    /// Remove and return the largest value in the tree.
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.root.pop_max()
+    }
+
+    /// Remove every value from the tree.
+    pub fn clear(&mut self) {
+        self.root.clear();
+    }
+}
+
+impl<T: Ord> Default for BinaryTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The operations below walk the tree with loops rather than recursion, so a
+// degenerate tree (e.g. built from sorted input) cannot overflow the stack.
+impl<T: Ord> SubTree<T> {
+    /// Insert `value`, returning whether it was not already present.
+    fn insert(&mut self, value: T) -> bool {
+        if self.has(&value) {
+            return false;
+        }
+        // every node on the way down gains one descendant
+        let mut subtree = self;
+        while let Some(ordering) = subtree.cmp_root(&value) {
+            let node = subtree.0.as_mut().expect("non-empty subtree");
+            node.size += 1;
+            subtree = if ordering == Ordering::Less {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+        // the empty subtree reached is where the value belongs
+        *subtree = SubTree(Some(Box::new(Node {
+            value,
+            size: 1,
+            left: SubTree(None),
+            right: SubTree(None),
+        })));
+        true
+    }
+
+    fn has(&self, value: &T) -> bool {
+        let mut subtree = self;
+        while let SubTree(Some(node)) = subtree {
+            subtree = match value.cmp(&node.value) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    /// Compare `value` against the value at the root of this subtree, or
+    /// `None` if the subtree is empty.
+    fn cmp_root(&self, value: &T) -> Option<Ordering> {
+        self.0.as_ref().map(|node| value.cmp(&node.value))
+    }
+
+    fn size(&self) -> usize {
+        self.0.as_ref().map_or(0, |node| node.size)
+    }
+
+    fn remove(&mut self, value: &T) -> Option<T> {
+        if !self.has(value) {
+            return None;
+        }
+        // every node above the removed one loses one descendant
+        let mut subtree = self;
+        while let Some(ordering) = subtree.cmp_root(value) {
+            if ordering == Ordering::Equal {
+                break;
+            }
+            let node = subtree.0.as_mut().expect("non-empty subtree");
+            node.size -= 1;
+            subtree = if ordering == Ordering::Less {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+        subtree.remove_root()
+    }
+
+    fn pop_min(&mut self) -> Option<T> {
+        let mut subtree = self;
+        while subtree.0.as_ref()?.left.0.is_some() {
+            let node = subtree.0.as_mut().expect("non-empty subtree");
+            node.size -= 1;
+            subtree = &mut node.left;
+        }
+        subtree.remove_root()
+    }
+
+    fn pop_max(&mut self) -> Option<T> {
+        let mut subtree = self;
+        while subtree.0.as_ref()?.right.0.is_some() {
+            let node = subtree.0.as_mut().expect("non-empty subtree");
+            node.size -= 1;
+            subtree = &mut node.right;
+        }
+        subtree.remove_root()
+    }
+
+    fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    /// Unlink the node at the root of this subtree and return its value.
+    fn remove_root(&mut self) -> Option<T> {
+        let node = self.0.as_mut()?;
+        if node.left.0.is_some() && node.right.0.is_some() {
+            // two children: the in-order successor (the smallest value of the
+            // right subtree) takes the place of the removed value
+            let successor = node.right.pop_min()?;
+            node.size -= 1;
+            return Some(std::mem::replace(&mut node.value, successor));
+        }
+        // zero or one child: the child (if any) moves up into this position
+        let mut node = self.0.take()?;
+        *self = if node.left.0.is_some() {
+            SubTree(node.left.0.take())
+        } else {
+            SubTree(node.right.0.take())
+        };
+        Some(node.value)
+    }
+}
+
+impl<T: Ord> Drop for SubTree<T> {
+    fn drop(&mut self) {
+        // detach the children before each node is freed, so that dropping a
+        // node never recurses into its subtrees
+        let mut stack: Vec<Box<Node<T>>> = self.0.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.0.take());
+            stack.extend(node.right.0.take());
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::tests::on_small_stack;
+
+    /// A tree with `values` inserted in order, so that the order shows in
+    /// its shape. Shared by the tests of the other tree modules.
+    pub(crate) fn tree_of<T: Ord + Clone>(values: &[T]) -> BinaryTree<T> {
+        let mut tree = BinaryTree::new();
+        for value in values {
+            tree.insert(value.clone());
+        }
+        tree
+    }
+
+    #[test]
+    fn remove_leaf_and_single_child() {
+        let mut tree = tree_of(&[5, 3, 8, 1]);
+        assert_eq!(tree.remove(&1), Some(1));
+        assert_eq!(tree.remove(&3), Some(3));
+        assert_eq!(tree.remove(&3), None);
+        assert_eq!(tree.len(), 2);
+        assert!(tree.has(&5) && tree.has(&8));
+    }
+
+    #[test]
+    fn remove_with_two_children() {
+        let mut tree = tree_of(&[5, 3, 8, 7, 9, 6]);
+        assert_eq!(tree.remove(&5), Some(5));
+        assert_eq!(tree.len(), 5);
+        for value in [3, 6, 7, 8, 9] {
+            assert!(tree.has(&value));
+        }
+        assert!(!tree.has(&5));
+    }
+
+    #[test]
+    fn pop_min_max_and_clear() {
+        let mut tree = tree_of(&[4, 2, 6, 1, 3, 5, 7]);
+        assert_eq!(tree.pop_min(), Some(1));
+        assert_eq!(tree.pop_max(), Some(7));
+        assert_eq!(tree.pop_min(), Some(2));
+        assert_eq!(tree.len(), 4);
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.pop_min(), None);
+        assert_eq!(tree.pop_max(), None);
+    }
+
+    #[test]
+    fn degenerate_tree_on_small_stack() {
+        on_small_stack(|| {
+            const N: usize = 2_000_000;
+            // build the shape sorted inserts would produce, a chain of
+            // right children, without paying for N linear-time inserts
+            let mut root = SubTree(None);
+            for value in (0..N).rev() {
+                root = SubTree(Some(Box::new(Node {
+                    value,
+                    size: N - value,
+                    left: SubTree(None),
+                    right: root,
+                })));
+            }
+            let mut tree = BinaryTree { root };
+            assert_eq!(tree.len(), N);
+            assert!(tree.has(&(N - 1)));
+            tree.insert(N);
+            assert_eq!(tree.pop_max(), Some(N));
+            assert_eq!(tree.remove(&(N - 2)), Some(N - 2));
+            assert_eq!(tree.pop_min(), Some(0));
+            assert_eq!(tree.len(), N - 2);
+            drop(tree);
+        });
+    }
+}
@@ -0,0 +1,251 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+#[derive(Debug)]
+struct Node<T: Ord> {
+    value: T,
+    left: SubTree<T>,
+    right: SubTree<T>,
+}
+
+// Cloning a subtree only bumps the reference count of its root.
+#[derive(Debug)]
+struct SubTree<T: Ord>(Option<Rc<Node<T>>>);
+
+impl<T: Ord> Clone for SubTree<T> {
+    fn clone(&self) -> Self {
+        SubTree(self.0.clone())
+    }
+}
+
+/// An immutable, structurally shared variant of [`BinaryTree`](super::BinaryTree).
+///
+/// `insert` and `remove` leave the tree untouched and return a new version
+/// instead. Only the nodes on the path to the change are copied; every other
+/// node is shared with the old version through an `Rc`, so keeping old
+/// versions around (e.g. as undo history) is cheap.
+#[derive(Debug)]
+pub struct PersistentTree<T: Ord> {
+    root: SubTree<T>,
+    len: usize,
+}
+
+impl<T: Ord> Clone for PersistentTree<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Ord + Clone> PersistentTree<T> {
+    pub fn new() -> Self {
+        Self {
+            root: SubTree(None),
+            len: 0,
+        }
+    }
+
+    /// A version of the tree that also contains `value`.
+    pub fn insert(&self, value: T) -> Self {
+        match self.root.insert(value) {
+            Some(root) => Self {
+                root,
+                len: self.len + 1,
+            },
+            None => self.clone(),
+        }
+    }
+
+    /// A version of the tree without `value`.
+    pub fn remove(&self, value: &T) -> Self {
+        match self.root.remove(value) {
+            Some(root) => Self {
+                root,
+                len: self.len - 1,
+            },
+            None => self.clone(),
+        }
+    }
+
+    pub fn has(&self, value: &T) -> bool {
+        let mut subtree = &self.root;
+        while let SubTree(Some(node)) = subtree {
+            subtree = match value.cmp(&node.value) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the values in ascending order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+}
+
+impl<T: Ord + Clone> Default for PersistentTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> SubTree<T> {
+    fn leaf(value: T) -> Self {
+        SubTree(Some(Rc::new(Node {
+            value,
+            left: SubTree(None),
+            right: SubTree(None),
+        })))
+    }
+
+    /// Copy of `node` with its children replaced.
+    fn with_children(node: &Node<T>, left: SubTree<T>, right: SubTree<T>) -> Self {
+        SubTree(Some(Rc::new(Node {
+            value: node.value.clone(),
+            left,
+            right,
+        })))
+    }
+
+    /// The new subtree with `value` inserted, or `None` if it is already
+    /// present and nothing needs to be copied.
+    fn insert(&self, value: T) -> Option<Self> {
+        let Some(node) = &self.0 else {
+            return Some(SubTree::leaf(value));
+        };
+        match value.cmp(&node.value) {
+            Ordering::Less => Some(SubTree::with_children(
+                node,
+                node.left.insert(value)?,
+                node.right.clone(),
+            )),
+            Ordering::Greater => Some(SubTree::with_children(
+                node,
+                node.left.clone(),
+                node.right.insert(value)?,
+            )),
+            Ordering::Equal => None,
+        }
+    }
+
+    /// The new subtree with `value` removed, or `None` if it is not present.
+    fn remove(&self, value: &T) -> Option<Self> {
+        let node = self.0.as_ref()?;
+        match value.cmp(&node.value) {
+            Ordering::Less => Some(SubTree::with_children(
+                node,
+                node.left.remove(value)?,
+                node.right.clone(),
+            )),
+            Ordering::Greater => Some(SubTree::with_children(
+                node,
+                node.left.clone(),
+                node.right.remove(value)?,
+            )),
+            Ordering::Equal => Some(match (&node.left.0, &node.right.0) {
+                (None, _) => node.right.clone(),
+                (_, None) => node.left.clone(),
+                // two children: the in-order successor takes the place of
+                // the removed value
+                (Some(_), Some(right)) => {
+                    let mut successor = right;
+                    while let SubTree(Some(left)) = &successor.left {
+                        successor = left;
+                    }
+                    SubTree(Some(Rc::new(Node {
+                        value: successor.value.clone(),
+                        left: node.left.clone(),
+                        right: node.right.remove(&successor.value)?,
+                    })))
+                }
+            }),
+        }
+    }
+}
+
+/// In-order iterator over a [`PersistentTree`], created by [`PersistentTree::iter`].
+pub struct Iter<'a, T: Ord> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T: Ord> Iter<'a, T> {
+    fn push_left_spine(&mut self, mut subtree: &'a SubTree<T>) {
+        while let SubTree(Some(node)) = subtree {
+            self.stack.push(node);
+            subtree = &node.left;
+        }
+    }
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some(&node.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root<T: Ord>(tree: &PersistentTree<T>) -> &Rc<Node<T>> {
+        tree.root.0.as_ref().unwrap()
+    }
+
+    #[test]
+    fn versions_are_independent() {
+        let v0 = PersistentTree::new();
+        let v1 = v0.insert(2).insert(1).insert(3);
+        let v2 = v1.insert(4);
+        let v3 = v2.remove(&2);
+        assert!(v0.is_empty());
+        assert_eq!(v1.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(v2.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(v3.iter().copied().collect::<Vec<_>>(), [1, 3, 4]);
+        assert_eq!(v3.len(), 3);
+        assert!(v2.has(&2) && !v3.has(&2));
+    }
+
+    #[test]
+    fn unchanged_nodes_are_shared() {
+        //     2
+        //    / \
+        //   1   3
+        let v1 = PersistentTree::new().insert(2).insert(1).insert(3);
+        let left = root(&v1).left.0.as_ref().unwrap();
+        assert_eq!(Rc::strong_count(left), 1);
+
+        // inserting on the right copies the root and the right spine only
+        let v2 = v1.insert(4);
+        assert!(!Rc::ptr_eq(root(&v1), root(&v2)));
+        assert_eq!(Rc::strong_count(left), 2);
+        assert!(Rc::ptr_eq(left, root(&v2).left.0.as_ref().unwrap()));
+
+        // a no-op shares the whole tree
+        let v3 = v2.insert(4);
+        assert!(Rc::ptr_eq(root(&v2), root(&v3)));
+        let v4 = v3.remove(&10);
+        assert_eq!(Rc::strong_count(root(&v2)), 3);
+
+        drop(v2);
+        drop(v3);
+        drop(v4);
+        assert_eq!(Rc::strong_count(left), 1);
+    }
+}
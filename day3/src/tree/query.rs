@@ -0,0 +1,261 @@
+use std::cmp::Ordering;
+use std::ops::{Bound, RangeBounds};
+
+use super::{BinaryTree, Node, SubTree};
+
+/// In-order iterator over the values within a range, created by
+/// [`BinaryTree::range`].
+pub struct Range<'a, T: Ord> {
+    // nodes at or above the lower bound whose value has not been yielded yet
+    stack: Vec<&'a Node<T>>,
+    // the node holding the largest value within the upper bound
+    last: Option<&'a Node<T>>,
+}
+
+impl<'a, T: Ord> Range<'a, T> {
+    fn push_left_spine(&mut self, mut subtree: &'a SubTree<T>) {
+        while let SubTree(Some(node)) = subtree {
+            self.stack.push(node);
+            subtree = &node.left;
+        }
+    }
+}
+
+impl<'a, T: Ord> Iterator for Range<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if self.last.is_some_and(|last| std::ptr::eq(node, last)) {
+            // every remaining value lies beyond the upper bound
+            self.stack.clear();
+        } else {
+            self.push_left_spine(&node.right);
+        }
+        Some(&node.value)
+    }
+}
+
+impl<T: Ord> BinaryTree<T> {
+    /// Iterate in ascending order over the values that lie within `range`.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        let mut iter = Range {
+            stack: Vec::new(),
+            last: None,
+        };
+        // descend towards the lower bound, remembering only the nodes that
+        // are not below it
+        let mut subtree = &self.root;
+        while let SubTree(Some(node)) = subtree {
+            let after_start = match range.start_bound() {
+                Bound::Included(start) => node.value >= *start,
+                Bound::Excluded(start) => node.value > *start,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                iter.stack.push(node);
+                subtree = &node.left;
+            } else {
+                subtree = &node.right;
+            }
+        }
+        // descend towards the upper bound to find where to stop
+        let mut subtree = &self.root;
+        while let SubTree(Some(node)) = subtree {
+            let before_end = match range.end_bound() {
+                Bound::Included(end) => node.value <= *end,
+                Bound::Excluded(end) => node.value < *end,
+                Bound::Unbounded => true,
+            };
+            if before_end {
+                iter.last = Some(node);
+                subtree = &node.right;
+            } else {
+                subtree = &node.left;
+            }
+        }
+        let empty = match (iter.stack.last(), iter.last) {
+            (Some(first), Some(last)) => first.value > last.value,
+            _ => true,
+        };
+        if empty {
+            iter.stack.clear();
+        }
+        iter
+    }
+
+    /// The smallest value in the tree.
+    pub fn first(&self) -> Option<&T> {
+        let mut node = self.root.0.as_deref()?;
+        while let SubTree(Some(left)) = &node.left {
+            node = left;
+        }
+        Some(&node.value)
+    }
+
+    /// The largest value in the tree.
+    pub fn last(&self) -> Option<&T> {
+        let mut node = self.root.0.as_deref()?;
+        while let SubTree(Some(right)) = &node.right {
+            node = right;
+        }
+        Some(&node.value)
+    }
+
+    /// The largest value less than or equal to `value`.
+    pub fn floor(&self, value: &T) -> Option<&T> {
+        self.root.closest(value, Ordering::Less, true)
+    }
+
+    /// The smallest value greater than or equal to `value`.
+    pub fn ceiling(&self, value: &T) -> Option<&T> {
+        self.root.closest(value, Ordering::Greater, true)
+    }
+
+    /// The smallest value strictly greater than `value`.
+    pub fn successor(&self, value: &T) -> Option<&T> {
+        self.root.closest(value, Ordering::Greater, false)
+    }
+
+    /// The largest value strictly less than `value`.
+    pub fn predecessor(&self, value: &T) -> Option<&T> {
+        self.root.closest(value, Ordering::Less, false)
+    }
+
+    /// The number of values in the tree that are smaller than `value`.
+    pub fn rank(&self, value: &T) -> usize {
+        let mut rank = 0;
+        let mut subtree = &self.root;
+        while let SubTree(Some(node)) = subtree {
+            match value.cmp(&node.value) {
+                Ordering::Less => subtree = &node.left,
+                Ordering::Equal => return rank + node.left.size(),
+                Ordering::Greater => {
+                    rank += node.left.size() + 1;
+                    subtree = &node.right;
+                }
+            }
+        }
+        rank
+    }
+
+    /// The `k`-th smallest value, counting from zero, so that `select(0)` is
+    /// the smallest and `select(len() / 2)` the (upper) median.
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut subtree = &self.root;
+        while let SubTree(Some(node)) = subtree {
+            let left_size = node.left.size();
+            match k.cmp(&left_size) {
+                Ordering::Less => subtree = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    subtree = &node.right;
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T: Ord> SubTree<T> {
+    /// Find the value closest to `value` on the `side` of it, which is either
+    /// `Ordering::Less` or `Ordering::Greater`. An equal value is accepted
+    /// only if `inclusive` is set.
+    fn closest(&self, value: &T, side: Ordering, inclusive: bool) -> Option<&T> {
+        let mut best = None;
+        let mut subtree = self;
+        while let SubTree(Some(node)) = subtree {
+            let ordering = node.value.cmp(value);
+            if ordering == Ordering::Equal && inclusive {
+                return Some(&node.value);
+            }
+            // a value on the wanted side is a candidate, and anything closer
+            // lies back towards `value`; otherwise keep moving towards `side`
+            let towards_side = if ordering == side {
+                best = Some(&node.value);
+                false
+            } else {
+                true
+            };
+            subtree = match (side, towards_side) {
+                (Ordering::Greater, true) | (Ordering::Less, false) => &node.right,
+                _ => &node.left,
+            };
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::tests::tree_of;
+
+    const SAMPLE: [i32; 7] = [40, 20, 60, 10, 30, 50, 70];
+
+    #[test]
+    fn range_bounds() {
+        let tree = tree_of(&SAMPLE);
+        let collect = |range: Range<'_, i32>| range.copied().collect::<Vec<_>>();
+        assert_eq!(collect(tree.range(20..50)), [20, 30, 40]);
+        assert_eq!(collect(tree.range(15..=50)), [20, 30, 40, 50]);
+        assert_eq!(collect(tree.range(55..)), [60, 70]);
+        assert_eq!(collect(tree.range(..30)), [10, 20]);
+        assert_eq!(
+            collect(tree.range((Bound::Excluded(20), Bound::Excluded(60)))),
+            [30, 40, 50]
+        );
+        assert_eq!(collect(tree.range(41..49)), []);
+        assert_eq!(
+            collect(tree.range((Bound::Included(50), Bound::Excluded(20)))),
+            []
+        );
+        assert_eq!(tree.range(..).count(), 7);
+    }
+
+    #[test]
+    fn nearest_lookups() {
+        let tree = tree_of(&SAMPLE);
+        assert_eq!(tree.first(), Some(&10));
+        assert_eq!(tree.last(), Some(&70));
+        assert_eq!(tree.floor(&35), Some(&30));
+        assert_eq!(tree.floor(&30), Some(&30));
+        assert_eq!(tree.floor(&5), None);
+        assert_eq!(tree.ceiling(&35), Some(&40));
+        assert_eq!(tree.ceiling(&40), Some(&40));
+        assert_eq!(tree.ceiling(&75), None);
+        assert_eq!(tree.successor(&40), Some(&50));
+        assert_eq!(tree.successor(&70), None);
+        assert_eq!(tree.predecessor(&40), Some(&30));
+        assert_eq!(tree.predecessor(&10), None);
+
+        let empty: BinaryTree<i32> = BinaryTree::new();
+        assert_eq!(empty.first(), None);
+        assert_eq!(empty.floor(&1), None);
+    }
+
+    #[test]
+    fn rank_and_select() {
+        let mut tree = tree_of(&SAMPLE);
+        assert_eq!(tree.rank(&10), 0);
+        assert_eq!(tree.rank(&40), 3);
+        assert_eq!(tree.rank(&45), 4);
+        assert_eq!(tree.rank(&99), 7);
+        assert_eq!(tree.select(0), Some(&10));
+        assert_eq!(tree.select(3), Some(&40));
+        assert_eq!(tree.select(6), Some(&70));
+        assert_eq!(tree.select(7), None);
+
+        // sizes stay correct through removals and duplicate inserts
+        tree.remove(&40);
+        tree.pop_min();
+        tree.insert(30);
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.select(2), Some(&50));
+        assert_eq!(tree.rank(&60), 3);
+        for (k, value) in tree.iter().enumerate() {
+            assert_eq!(tree.select(k), Some(value));
+            assert_eq!(tree.rank(value), k);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt::{self, Display, Write};
+
+use super::{BinaryTree, Node, SubTree};
+
+/// Which child of its parent a node is.
+#[derive(Clone, Copy)]
+enum Side {
+    Root,
+    Left,
+    Right,
+}
+
+enum Step<'a, T: Ord> {
+    /// Lay out the subtree rooted at the node.
+    Visit(&'a Node<T>, String, Side),
+    /// Print the line of a single node.
+    Line(&'a T, String, Side),
+}
+
+/// `value` as a quoted Graphviz string.
+fn dot_quoted(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl<T: Ord + Display> BinaryTree<T> {
+    /// Render the shape of the tree as a Graphviz digraph, e.g. for
+    /// `dot -Tsvg`. Missing children that have a sibling are drawn as points,
+    /// so left and right children can be told apart.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        let mut next_id = 0;
+        let mut stack: Vec<(&Node<T>, usize)> = Vec::new();
+        if let SubTree(Some(root)) = &self.root {
+            stack.push((root, next_id));
+            next_id += 1;
+        }
+        while let Some((node, id)) = stack.pop() {
+            let label = dot_quoted(&node.value.to_string());
+            writeln!(dot, "    n{id} [label={label}];").unwrap();
+            if node.left.0.is_none() && node.right.0.is_none() {
+                continue;
+            }
+            let (left_id, right_id) = (next_id, next_id + 1);
+            next_id += 2;
+            // push right first so that the left subtree is written first
+            for (child, child_id) in [(&node.right, right_id), (&node.left, left_id)] {
+                match child {
+                    SubTree(Some(child)) => stack.push((child, child_id)),
+                    SubTree(None) => {
+                        writeln!(dot, "    n{child_id} [shape=point];").unwrap();
+                    }
+                }
+            }
+            writeln!(dot, "    n{id} -> n{left_id};").unwrap();
+            writeln!(dot, "    n{id} -> n{right_id};").unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Draws the tree sideways, with the root on the left, larger values above
+/// and smaller values below it:
+///
+/// ```text
+///     /-- 7
+/// /-- 6
+/// |   \-- 5
+/// 4
+/// |   /-- 3
+/// \-- 2
+///     \-- 1
+/// ```
+impl<T: Ord + Display> Display for BinaryTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut stack = Vec::new();
+        if let SubTree(Some(root)) = &self.root {
+            stack.push(Step::Visit(root, String::new(), Side::Root));
+        }
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Line(value, prefix, side) => {
+                    let connector = match side {
+                        Side::Root => "",
+                        Side::Left => "\\-- ",
+                        Side::Right => "/-- ",
+                    };
+                    writeln!(f, "{prefix}{connector}{value}")?;
+                }
+                Step::Visit(node, prefix, side) => {
+                    // a vertical bar joins the node to its parent, which lies
+                    // below a right child and above a left child
+                    let (above, below) = match side {
+                        Side::Root => ("", ""),
+                        Side::Left => ("|   ", "    "),
+                        Side::Right => ("    ", "|   "),
+                    };
+                    // pushed in reverse: right subtree, node, left subtree
+                    if let SubTree(Some(left)) = &node.left {
+                        stack.push(Step::Visit(left, format!("{prefix}{below}"), Side::Left));
+                    }
+                    let right_prefix = format!("{prefix}{above}");
+                    stack.push(Step::Line(&node.value, prefix, side));
+                    if let SubTree(Some(right)) = &node.right {
+                        stack.push(Step::Visit(right, right_prefix, Side::Right));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::tests::tree_of;
+
+    #[test]
+    fn ascii_snapshot() {
+        let tree = tree_of(&[4, 2, 6, 1, 3, 5, 7]);
+        assert_eq!(
+            tree.to_string(),
+            concat!(
+                "    /-- 7\n",
+                "/-- 6\n",
+                "|   \\-- 5\n",
+                "4\n",
+                "|   /-- 3\n",
+                "\\-- 2\n",
+                "    \\-- 1\n",
+            )
+        );
+
+        // insertion order shows up in the shape
+        let tree = tree_of(&[1, 3, 2]);
+        assert_eq!(tree.to_string(), concat!("/-- 3\n", "|   \\-- 2\n", "1\n"));
+        assert_eq!(tree_of::<i32>(&[]).to_string(), "");
+    }
+
+    #[test]
+    fn dot_snapshot() {
+        let tree = tree_of(&[2, 1, 3, 4]);
+        assert_eq!(
+            tree.to_dot(),
+            concat!(
+                "digraph {\n",
+                "    n0 [label=\"2\"];\n",
+                "    n0 -> n1;\n",
+                "    n0 -> n2;\n",
+                "    n1 [label=\"1\"];\n",
+                "    n2 [label=\"3\"];\n",
+                "    n3 [shape=point];\n",
+                "    n2 -> n3;\n",
+                "    n2 -> n4;\n",
+                "    n4 [label=\"4\"];\n",
+                "}\n",
+            )
+        );
+
+        let mut tree = BinaryTree::new();
+        tree.insert(String::from(r#"say "hi""#));
+        tree.insert(String::from(r"C:\dir"));
+        assert_eq!(
+            tree.to_dot(),
+            concat!(
+                "digraph {\n",
+                "    n0 [label=\"say \\\"hi\\\"\"];\n",
+                "    n2 [shape=point];\n",
+                "    n0 -> n1;\n",
+                "    n0 -> n2;\n",
+                "    n1 [label=\"C:\\\\dir\"];\n",
+                "}\n",
+            )
+        );
+    }
+}
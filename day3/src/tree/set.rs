@@ -0,0 +1,172 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+use super::BinaryTree;
+use super::iter::Iter;
+
+/// Walks two trees side by side in ascending order, pairing up equal values.
+struct Merge<'a, T: Ord> {
+    a: Peekable<Iter<'a, T>>,
+    b: Peekable<Iter<'a, T>>,
+}
+
+impl<'a, T: Ord> Merge<'a, T> {
+    fn new(a: &'a BinaryTree<T>, b: &'a BinaryTree<T>) -> Self {
+        Merge {
+            a: a.iter().peekable(),
+            b: b.iter().peekable(),
+        }
+    }
+
+    /// The next smallest value, from the first tree, the second or both.
+    fn next_pair(&mut self) -> Option<(Option<&'a T>, Option<&'a T>)> {
+        let ordering = match (self.a.peek(), self.b.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(a), Some(b)) => a.cmp(b),
+        };
+        Some(match ordering {
+            Ordering::Less => (self.a.next(), None),
+            Ordering::Greater => (None, self.b.next()),
+            Ordering::Equal => (self.a.next(), self.b.next()),
+        })
+    }
+}
+
+/// Values in either tree, created by [`BinaryTree::union`].
+pub struct Union<'a, T: Ord>(Merge<'a, T>);
+
+impl<'a, T: Ord> Iterator for Union<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (a, b) = self.0.next_pair()?;
+        a.or(b)
+    }
+}
+
+/// Values in both trees, created by [`BinaryTree::intersection`].
+pub struct Intersection<'a, T: Ord>(Merge<'a, T>);
+
+impl<'a, T: Ord> Iterator for Intersection<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (Some(a), Some(_)) = self.0.next_pair()? {
+                return Some(a);
+            }
+        }
+    }
+}
+
+/// Values in the first tree but not the second, created by
+/// [`BinaryTree::difference`].
+pub struct Difference<'a, T: Ord>(Merge<'a, T>);
+
+impl<'a, T: Ord> Iterator for Difference<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (Some(a), None) = self.0.next_pair()? {
+                return Some(a);
+            }
+        }
+    }
+}
+
+/// Values in exactly one of the trees, created by
+/// [`BinaryTree::symmetric_difference`].
+pub struct SymmetricDifference<'a, T: Ord>(Merge<'a, T>);
+
+impl<'a, T: Ord> Iterator for SymmetricDifference<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.0.next_pair()? {
+                (Some(a), None) => return Some(a),
+                (None, Some(b)) => return Some(b),
+                _ => {}
+            }
+        }
+    }
+}
+
+// Each operation is a single pass over both trees in order, so it takes
+// O(n + m) time rather than O(n log m) for repeated `has` calls.
+impl<T: Ord> BinaryTree<T> {
+    /// Iterate in ascending order over the values in `self` or `other`.
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T> {
+        Union(Merge::new(self, other))
+    }
+
+    /// Iterate in ascending order over the values in both `self` and `other`.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T> {
+        Intersection(Merge::new(self, other))
+    }
+
+    /// Iterate in ascending order over the values in `self` but not in `other`.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T> {
+        Difference(Merge::new(self, other))
+    }
+
+    /// Iterate in ascending order over the values in exactly one of `self`
+    /// and `other`.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T> {
+        SymmetricDifference(Merge::new(self, other))
+    }
+
+    /// Whether every value of `self` is also in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+
+    /// Whether every value of `other` is also in `self`.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Whether `self` and `other` have no values in common.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::tests::tree_of;
+
+    #[test]
+    fn set_operations() {
+        let a = tree_of(&["log", "serde", "base64", "rand"]);
+        let b = tree_of(&["serde", "tokio", "log", "futures"]);
+        let collect =
+            |iter: &mut dyn Iterator<Item = &&'static str>| iter.copied().collect::<Vec<_>>();
+        assert_eq!(
+            collect(&mut a.union(&b)),
+            ["base64", "futures", "log", "rand", "serde", "tokio"]
+        );
+        assert_eq!(collect(&mut a.intersection(&b)), ["log", "serde"]);
+        assert_eq!(collect(&mut a.difference(&b)), ["base64", "rand"]);
+        assert_eq!(collect(&mut b.difference(&a)), ["futures", "tokio"]);
+        assert_eq!(
+            collect(&mut a.symmetric_difference(&b)),
+            ["base64", "futures", "rand", "tokio"]
+        );
+    }
+
+    #[test]
+    fn subset_and_disjoint() {
+        let all = tree_of(&["a", "b", "c", "d"]);
+        let some = tree_of(&["b", "d"]);
+        let other = tree_of(&["e", "f"]);
+        let empty = tree_of(&[]);
+        assert!(some.is_subset(&all));
+        assert!(all.is_superset(&some));
+        assert!(!all.is_subset(&some));
+        assert!(empty.is_subset(&some));
+        assert!(all.is_subset(&all));
+        assert!(all.is_disjoint(&other));
+        assert!(!all.is_disjoint(&some));
+        assert!(empty.is_disjoint(&empty));
+    }
+}